@@ -1,7 +1,34 @@
 pub const THREE_MONTHS_SECONDS: i64 = 60 * 60 * 24 * 90;
 pub const MAX_MILESTONES: usize = 8;
 pub const MAX_USERS: usize = 1000;
-pub const MAX_HOLD_AMOUNT: u64 = 50000000; // Anti-whale restriction:
+pub const DEFAULT_HOLD_CAP_BPS: u64 = 100; // Anti-whale restriction: 1% of the supply per wallet
 pub const MAX_EXEMPTED_WALLETS: usize = 20; // Maximum exempted wallets
+pub const BPS_DENOMINATOR: u64 = 10_000; // 100% expressed in basis points
+pub const MAX_TAX_BPS: u16 = 1_000; // Unlock tax can never exceed 10%
+pub const DEFAULT_TAX_BPS: u16 = 150; // 1.5% unlock tax
+pub const DEFAULT_BURN_SHARE_BPS: u16 = 5_000; // 50% of the tax goes to burn
+pub const DEFAULT_MARKETING_SHARE_BPS: u16 = 5_000; // 50% of the tax goes to marketing
+pub const MAX_CRANK_REWARD: u64 = 1_000_000_000_000; // Upper bound on the keeper reward per crank
+pub const MAX_MARKET_CAP: u64 = 10_000_000; // Sanity cap on any market cap reading
+pub const DEFAULT_MAX_PRICE_STALENESS: i64 = 60; // Oracle prices older than a minute are rejected
+pub const DEFAULT_MAX_PRICE_CONFIDENCE_BPS: u16 = 200; // Confidence interval must be within 2% of price
+pub const MAX_OBSERVATIONS: usize = 32; // Market cap observations kept for the TWAP
+pub const OBSERVATIONS_PER_WINDOW: i64 = 16; // Observation spacing is the TWAP window split this many ways
+pub const DEFAULT_TWAP_WINDOW_SECONDS: i64 = 60 * 30; // Milestones look at a 30 minute TWAP
+pub const MAX_TWAP_WINDOW_SECONDS: i64 = 60 * 60 * 24 * 7; // TWAP window can be at most a week
+pub const GLOBAL_STATE_SEED: &[u8] = b"global_state"; // Seed for the per-mint GlobalState PDA
+pub const ROLES_SEED: &[u8] = b"roles"; // Seed for the per-campaign Roles PDA
+pub const MULTISIG_SEED: &[u8] = b"multisig"; // Seed for the per-campaign Multisig PDA
+pub const PROPOSAL_SEED: &[u8] = b"proposal"; // Seed for AdminProposal PDAs, followed by the multisig and index
+pub const MAX_MULTISIG_SIGNERS: usize = 10; // Largest multisig signer set
+pub const USER_STATE_SEED: &[u8] = b"user_state"; // Seed for per-user MilestoneUnlockAccount PDAs
+pub const VAULT_SEED: &[u8] = b"vault"; // Seed for the token vault holding the locked supply
+pub const VAULT_AUTHORITY_SEED: &[u8] = b"vault_authority"; // Seed for the PDA that owns the vault
+pub const EXTRA_ACCOUNT_METAS_SEED: &[u8] = b"extra-account-metas"; // Seed of the transfer hook's ExtraAccountMetaList PDA
+pub const EXTRA_ACCOUNT_METAS_COUNT: usize = 2; // Global state and the source owner's user state
+pub const RAYDIUM_AMM_AUTHORITY_SEED: &[u8] = b"amm authority"; // Seed of the Raydium AMM v4 authority PDA
+pub const RAYDIUM_SWAP_BASE_IN_TAG: u8 = 9; // Raydium AMM v4 `swap_base_in` instruction tag
 // Constants for Milestone size
-pub const MILESTONE_SIZE: usize = 8 + 1; // u64 (8 bytes) + u8 (1 byte)
\ No newline at end of file
+pub const MILESTONE_SIZE: usize = 8 + 1 + 4 + 8; // u64 (8 bytes) + u8 (1 byte) + u32 (4 bytes) + u64 (8 bytes)
+// Constants for MarketCapObservation size
+pub const OBSERVATION_SIZE: usize = 8 + 8 + 16; // i64 + u64 + u128
\ No newline at end of file
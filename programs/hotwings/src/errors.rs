@@ -41,4 +41,78 @@ pub enum ErrorCode {
     LiquidityPoolNotSet,
     #[msg("Mileston has finished.")]
     MiletoneCompleted,
-}
\ No newline at end of file
+    #[msg("Invalid milestone schedule")]
+    InvalidMilestoneSchedule,
+    #[msg("Milestone already reached and cannot be changed")]
+    MilestoneAlreadyReached,
+    #[msg("User state account does not match the expected PDA")]
+    InvalidUserState,
+    #[msg("Invalid tax configuration")]
+    InvalidTaxConfig,
+    #[msg("Invalid treasury wallet account")]
+    InvalidTreasuryWallet,
+    #[msg("Invalid vault account")]
+    InvalidVault,
+    #[msg("Crank reward exceeds the allowed maximum")]
+    InvalidCrankReward,
+    #[msg("Invalid price feed account")]
+    InvalidPriceFeed,
+    #[msg("Price feed is not trading")]
+    PriceUnavailable,
+    #[msg("Price feed is stale")]
+    StalePrice,
+    #[msg("Price confidence interval is too wide")]
+    PriceConfidenceTooWide,
+    #[msg("Invalid liquidity pool account")]
+    InvalidLiquidityPool,
+    #[msg("Pool vault does not match the liquidity pool")]
+    InvalidPoolVault,
+    #[msg("Invalid TWAP window")]
+    InvalidTwapWindow,
+    #[msg("Not enough market cap observations to cover the TWAP window")]
+    InsufficientObservations,
+    #[msg("Nothing to claim until the next milestone")]
+    NothingToClaim,
+    #[msg("Invalid anti-whale hold cap")]
+    InvalidHoldCap,
+    #[msg("Amount exceeds the anti-whale hold cap")]
+    HoldCapExceeded,
+    #[msg("Transfer hook called outside of a Token-2022 transfer")]
+    NotTransferring,
+    #[msg("Invalid extra account meta list")]
+    InvalidExtraAccountMetaList,
+    #[msg("Invalid pending authority")]
+    InvalidPendingAuthority,
+    #[msg("No authority transfer is pending")]
+    NoPendingAuthority,
+    #[msg("Invalid role holder")]
+    InvalidRoleHolder,
+    #[msg("Role is not assigned")]
+    RoleNotAssigned,
+    #[msg("This change must go through the multisig")]
+    MultisigRequired,
+    #[msg("Invalid multisig signers or threshold")]
+    InvalidMultisigConfig,
+    #[msg("Signer is not part of the multisig")]
+    NotMultisigSigner,
+    #[msg("Invalid proposal")]
+    InvalidProposal,
+    #[msg("Signer already approved this proposal")]
+    AlreadyApproved,
+    #[msg("Proposal has already been executed")]
+    ProposalAlreadyExecuted,
+    #[msg("Not enough approvals to execute the proposal")]
+    ThresholdNotMet,
+    #[msg("Auto-sell is only allowed once the unlock is complete")]
+    UnlockNotComplete,
+    #[msg("Swap needs a minimum output")]
+    InvalidMinimumAmountOut,
+    #[msg("User allocation must be greater than zero")]
+    InvalidAllocation,
+    #[msg("Auto-sell has already been executed")]
+    AutoSellAlreadyExecuted,
+    #[msg("Vault holds nothing beyond user allocations and crank rewards")]
+    NothingToSell,
+    #[msg("This program cannot be its own mint's transfer hook, vault payouts would re-enter it")]
+    SelfTransferHook,
+}
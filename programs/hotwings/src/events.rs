@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::structs::{ AdminAction, BurnMode, HoldCapMode, Milestone, Role };
 
 #[event]
 pub struct UserRegistered {
@@ -21,6 +22,149 @@ pub struct MilestoneProcessed {
     pub unlocked_tokens: u64,
     pub milestone_index: u8,
     pub tax: u64,
+    pub tax_bps: u16,
     pub burn_tax: u64,
     pub marketing_tax: u64,
-}
\ No newline at end of file
+    pub treasury_tax: u64,
+    pub total_burned: u64,
+}
+
+#[event]
+pub struct MilestonesUpdated {
+    pub authority: Pubkey,          // Authority who set the schedule
+    pub milestones: Vec<Milestone>, // The full milestone schedule after the change
+}
+
+#[event]
+pub struct TaxConfigUpdated {
+    pub authority: Pubkey,
+    pub tax_bps: u16,             // Total unlock tax
+    pub burn_share_bps: u16,      // Share of the tax sent to burn
+    pub marketing_share_bps: u16, // Share of the tax sent to marketing
+    pub treasury_share_bps: u16,  // Share of the tax sent to the treasury
+    pub treasury_wallet: Pubkey,
+}
+
+#[event]
+pub struct HoldCapUpdated {
+    pub authority: Pubkey,
+    pub hold_cap_mode: HoldCapMode, // How `hold_cap` is interpreted
+    pub hold_cap: u64,              // Raw amount, basis points of supply or whole tokens
+}
+
+#[event]
+pub struct BurnModeUpdated {
+    pub authority: Pubkey,
+    pub burn_mode: BurnMode, // How the burn share of the tax is handled from now on
+    pub total_burned: u64,   // Tokens burned so far
+}
+
+#[event]
+pub struct VaultDeposited {
+    pub depositor: Pubkey,  // Wallet that funded the vault
+    pub amount: u64,        // Tokens deposited
+    pub vault_balance: u64, // Vault balance after the deposit
+}
+
+#[event]
+pub struct CrankRewardPaid {
+    pub caller: Pubkey,       // Keeper that ran the crank
+    pub reward: u64,          // Tokens paid out of the crank reward pool
+    pub milestone_index: u8,  // Milestone reached by this crank
+}
+
+#[event]
+pub struct AutoSellExecuted {
+    pub authority: Pubkey,        // Signer that triggered the sale
+    pub amount_in: u64,           // Vault tokens swapped
+    pub minimum_amount_out: u64,  // Slippage floor passed to Raydium
+}
+
+#[event]
+pub struct PythConfigUpdated {
+    pub authority: Pubkey,
+    pub price_feed: Pubkey,            // Pyth price account used for market cap updates
+    pub max_price_staleness: i64,      // Max price age in seconds
+    pub max_price_confidence_bps: u16, // Max confidence interval relative to price
+}
+
+#[event]
+pub struct MarketCapObserved {
+    pub market_cap: u64, // Market cap stored in the TWAP ring buffer
+    pub timestamp: i64,  // Time of the observation
+}
+
+#[event]
+pub struct UnlockDeferred {
+    pub wallet: Pubkey,
+    pub milestone_index: u8,  // Milestone being unlocked when the cap applied
+    pub deferred_tokens: u64, // Total now held back for this user
+}
+
+#[event]
+pub struct DeferredReleased {
+    pub wallet: Pubkey,
+    pub released_tokens: u64,    // Previously deferred tokens paid out
+    pub remaining_deferred: u64, // Still held back after this release
+}
+
+#[event]
+pub struct AuthorityProposed {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey, // Must sign accept_authority to take over
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferCancelled {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey, // Proposal that was dropped
+}
+
+#[event]
+pub struct RoleGranted {
+    pub authority: Pubkey,
+    pub role: Role,
+    pub holder: Pubkey, // New holder of the role
+}
+
+#[event]
+pub struct RoleRevoked {
+    pub authority: Pubkey,
+    pub role: Role,
+    pub previous_holder: Pubkey, // Holder the role was taken from
+}
+
+#[event]
+pub struct MultisigUpdated {
+    pub multisig: Pubkey,
+    pub signers: Vec<Pubkey>, // Signer set after the change
+    pub threshold: u8,        // Approvals needed to execute
+}
+
+#[event]
+pub struct AdminActionProposed {
+    pub proposal: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub action: AdminAction,
+}
+
+#[event]
+pub struct AdminActionApproved {
+    pub proposal: Pubkey,
+    pub signer: Pubkey,
+    pub approvals: u8, // Approvals recorded so far
+}
+
+#[event]
+pub struct AdminActionExecuted {
+    pub proposal: Pubkey,
+    pub executor: Pubkey,
+    pub action: AdminAction,
+}
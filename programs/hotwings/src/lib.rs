@@ -1,8 +1,8 @@
 #![allow(unexpected_cfgs)]
 
 use anchor_lang::prelude::*;
-use anchor_spl::associated_token::get_associated_token_address; 
-use spl_token::solana_program::program_pack::Pack;
+use anchor_lang::system_program;
+use anchor_spl::token_interface;
 use solana_program::{
     account_info::AccountInfo,
     program_error::ProgramError,
@@ -10,7 +10,17 @@ use solana_program::{
     instruction::Instruction,
 }; 
 use solana_program::program::invoke;
-use spl_token_2022::id as token_2022_program_id;
+use spl_token_2022::extension::{
+    transfer_hook::{ self, TransferHookAccount },
+    BaseStateWithExtensions,
+    StateWithExtensions,
+};
+use spl_tlv_account_resolution::{ account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList };
+use spl_type_length_value::state::TlvStateBorrowed;
+use spl_transfer_hook_interface::{
+    get_extra_account_metas_address,
+    instruction::{ ExecuteInstruction, TransferHookInstruction },
+};
 
 use crate::consts::*;
 use crate::structs::*;
@@ -27,11 +37,15 @@ declare_id!("L1dCurNdHKSmpRHFKGcaNf64qzExvCMGuZbU3uun6ow");
 /// Program module
 #[program]
 pub mod hotwings {
-    use anchor_spl::{associated_token::get_associated_token_address, token};
+    use anchor_spl::associated_token::get_associated_token_address_with_program_id;
     use super::*;
 
     /// Initialize the program with milestones and setup global state
-    pub fn initialize_program(ctx: Context<InitializeProgram>, raydium_program_id: Pubkey) -> Result<()> {
+    pub fn initialize_program(
+        ctx: Context<InitializeProgram>,
+        raydium_program_id: Pubkey,
+        milestones: Vec<Milestone>,
+    ) -> Result<()> {
         let global_state = &mut ctx.accounts.global_state;
 
         // Runtime validations for wallets
@@ -70,29 +84,59 @@ pub mod hotwings {
             return Err(ErrorCode::InvalidProjectWallet.into());
         }
 
+        global_state.authority = ctx.accounts.authority.key(); // Signer becomes the admin
+        global_state.bump = ctx.bumps.global_state;
         global_state.token_mint = ctx.accounts.token_mint.key(); 
+        global_state.token_decimals = ctx.accounts.token_mint.decimals;
+        global_state.pending_authority = Pubkey::default(); // No authority transfer in progress
+        global_state.multisig = Pubkey::default(); // Single-key admin until a multisig is created
         global_state.burn_wallet = ctx.accounts.burn_wallet.key();
         global_state.marketing_wallet = ctx.accounts.marketing_wallet.key();
         global_state.project_wallet = ctx.accounts.project_wallet.key(); 
+        global_state.vault = ctx.accounts.vault.key();
+        global_state.vault_authority_bump = ctx.bumps.vault_authority;
         global_state.current_market_cap = 0;
         global_state.current_milestone = 0;
         global_state.user_count = 0;
         global_state.three_month_unlock_date = Clock::get()?.unix_timestamp + THREE_MONTHS_SECONDS;
         global_state.unlock_complete = false;
         global_state.exempted_wallets = Vec::new();
+        global_state.tax_bps = DEFAULT_TAX_BPS;
+        global_state.burn_share_bps = DEFAULT_BURN_SHARE_BPS;
+        global_state.marketing_share_bps = DEFAULT_MARKETING_SHARE_BPS;
+        global_state.treasury_share_bps = 0;
+        global_state.treasury_wallet = Pubkey::default();
+        global_state.burn_mode = BurnMode::TransferToBurnWallet;
+        global_state.total_burned = 0;
+        global_state.crank_reward = 0;
+        global_state.crank_reward_pool = 0;
+        global_state.outstanding_allocations = 0;
+        global_state.auto_sold = false;
+        global_state.pyth_price_feed = Pubkey::default();
+        global_state.max_price_staleness = DEFAULT_MAX_PRICE_STALENESS;
+        global_state.max_price_confidence_bps = DEFAULT_MAX_PRICE_CONFIDENCE_BPS;
+        global_state.twap_window_seconds = DEFAULT_TWAP_WINDOW_SECONDS;
+        global_state.hold_cap_mode = HoldCapMode::SupplyBps;
+        global_state.hold_cap = DEFAULT_HOLD_CAP_BPS;
         msg!("Global state initialized with admin authority: {:?}", global_state.authority);
+
+        // The initializer holds every role until it hands them out
+        let roles = &mut ctx.accounts.roles;
+        roles.global_state = global_state.key();
+        roles.config_admin = global_state.authority;
+        roles.oracle_updater = global_state.authority;
+        roles.exemptions_manager = global_state.authority;
+        roles.treasury_signer = global_state.authority;
+        roles.bump = ctx.bumps.roles;
         
         // Milestones
-        global_state.milestones = [
-            Milestone { market_cap: 45_000, unlock_percent: 10 },
-            Milestone { market_cap: 105_500, unlock_percent: 20 },
-            Milestone { market_cap: 225_000, unlock_percent: 30 },
-            Milestone { market_cap: 395_000, unlock_percent: 40 },
-            Milestone { market_cap: 650_000, unlock_percent: 50 },
-            Milestone { market_cap: 997_000, unlock_percent: 60 },
-            Milestone { market_cap: 1_574_000, unlock_percent: 70 },
-            Milestone { market_cap: 2_500_000, unlock_percent: 100 },
-        ];
+        validate_milestones(&milestones, global_state.hold_cap_mode, global_state.token_decimals)?;
+        set_milestones(global_state, &milestones);
+
+        emit!(MilestonesUpdated {
+            authority: ctx.accounts.authority.key(),
+            milestones,
+        });
 
         global_state.raydium_program_id = raydium_program_id;
         msg!("Global state initialized successfully with Raydium program ID: {:?}", raydium_program_id);
@@ -101,86 +145,113 @@ pub mod hotwings {
     }
 
     /// Registers multiple users and creates their token accounts (if missing)
-    pub fn register_users(ctx: Context<RegisterUsers>, entries: Vec<UserEntry>) -> Result<()> {
+    pub fn register_users<'info>(
+        ctx: Context<'_, '_, '_, 'info, RegisterUsers<'info>>,
+        entries: Vec<UserEntry>,
+    ) -> Result<()> {
         let global_state = &mut ctx.accounts.global_state;
     
+        let hold_cap = effective_hold_cap(global_state, ctx.accounts.token_mint.supply)?;
+
         // Validate the number of users to ensure it doesn't exceed the maximum
         require!(
             global_state.user_count as usize + entries.len() <= MAX_USERS,
             ErrorCode::MaxUsersReached
         );
-    
-        for entry in entries.iter() {
 
-            let mut user_exists = false;
+        // Every entry needs its user state PDA, then its wallet and ATA, in remaining accounts
+        require!(
+            ctx.remaining_accounts.len() == entries.len() * 3,
+            ErrorCode::AccountNotEnough
+        );
+        let (user_state_accounts, wallet_accounts) = ctx.remaining_accounts.split_at(entries.len());
+    
+        for ((entry, user_state_info), wallet_pair) in
+            entries.iter().zip(user_state_accounts.iter()).zip(wallet_accounts.chunks(2))
+        {
+            let (wallet_info, ata_info) = (&wallet_pair[0], &wallet_pair[1]);
 
-            // Iterate over remaining accounts to ensure user is not already registered
-            for account_info in ctx.remaining_accounts.iter() {
-                if account_info.key == &entry.wallet {
-                    user_exists = true;
-                    break; // Exit the loop early if we find the user
-                }
-            }
+            // Derive the user's PDA with the same seeds used by the transfer hook
+            let (user_pda, bump) = Pubkey::find_program_address(
+                &[USER_STATE_SEED, global_state.key().as_ref(), entry.wallet.as_ref()],
+                ctx.program_id,
+            );
+            require!(user_state_info.key == &user_pda, ErrorCode::InvalidUserState);
 
-            // If the user is already registered, throw an error
-            require!(!user_exists, ErrorCode::UserAlreadyRegistered);
+            // If the PDA already holds data, the user is already registered
+            require!(user_state_info.data_is_empty(), ErrorCode::UserAlreadyRegistered);
 
             // Validate locked token amount (non-zero and reasonable)
-            require!(
-                entry.locked_tokens > 0,
-                ErrorCode::ArithmeticOverflow // Or create an ErrorCode like InvalidLockedTokenAmount
-            );
+            require!(entry.locked_tokens > 0, ErrorCode::InvalidAllocation);
 
             require!(
-                entry.locked_tokens <= MAX_HOLD_AMOUNT,
-                ErrorCode::ArithmeticOverflow // Or another custom error code
+                entry.locked_tokens <= hold_cap || global_state.exempted_wallets.contains(&entry.wallet),
+                ErrorCode::HoldCapExceeded
             );
 
-            // Derive the user's associated token account (ATA)
-            let user_ata = get_associated_token_address(&entry.wallet, &global_state.token_mint);
-    
-            // Create the user's associated token account if it doesn't exist
-            if ctx.remaining_accounts.iter().find(|acc| acc.key == &user_ata).is_none() {
-                let ata_instruction = spl_associated_token_account::instruction::create_associated_token_account(
-                    &ctx.accounts.authority.key(),
-                    &entry.wallet,
-                    &global_state.token_mint,
-                    &spl_associated_token_account::ID,
-                );
-            
-                // Verify that all accounts required by the ATA creation process are valid
-                // Ensure authority is a signer
-                require!(ctx.accounts.authority.is_signer, ErrorCode::Unauthorized);
-            
-                // Cross-program invocation (CPI) for creating the ATA
-                anchor_lang::solana_program::program::invoke(
-                    &ata_instruction,
-                    &[
-                        ctx.accounts.authority.to_account_info(), // Validate payer authority
-                        global_state.to_account_info(),
-                        ctx.accounts.system_program.to_account_info(),
-                        ctx.accounts.rent.to_account_info(), // Rent validation is implicit here
-                        ctx.accounts.token_program.to_account_info(),
-                    ],
-                ).map_err(|_| ErrorCode::TokenAccountCreationFailed)?;
-            
-                msg!("Successfully created ATA for user: {:?}", entry.wallet);
-            }
-    
-            // Initialize the user's PDA (MilestoneUnlockAccount)
-            let user_state = &mut ctx.accounts.user_state;
+            // Derive the user's associated token account (ATA) under the mint's token program
+            let user_ata = get_associated_token_address_with_program_id(
+                &entry.wallet,
+                &global_state.token_mint,
+                &ctx.accounts.token_program.key(),
+            );
     
-            user_state.wallet = entry.wallet;  // Store user's main wallet address
-            user_state.ata = user_ata;         // Store user's ATA address
-            user_state.total_locked_tokens = entry.locked_tokens; // Store locked tokens
-            user_state.unlocked_tokens = 0;   // Initially, no tokens unlocked
-            user_state.last_unlocked_milestone = 0; // No milestones processed
+            require!(wallet_info.key == &entry.wallet, ErrorCode::UserNotFound);
+            require!(ata_info.key == &user_ata, ErrorCode::UserWalletNotFound);
+
+            // Create the user's associated token account; a no-op if the user already has one
+            let ata_instruction = spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                &ctx.accounts.authority.key(),
+                &entry.wallet,
+                &global_state.token_mint,
+                &ctx.accounts.token_program.key(),
+            );
+            invoke(
+                &ata_instruction,
+                &[
+                    ctx.accounts.authority.to_account_info(), // Pays for a new ATA
+                    ata_info.clone(),
+                    wallet_info.clone(),
+                    ctx.accounts.token_mint.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                    ctx.accounts.token_program.to_account_info(),
+                    ctx.accounts.associated_token_program.to_account_info(),
+                ],
+            ).map_err(|_| ErrorCode::TokenAccountCreationFailed)?;
+
+            // Create the user's PDA (MilestoneUnlockAccount), paid for by the authority
+            create_user_state_account(
+                &ctx.accounts.authority.to_account_info(),
+                user_state_info,
+                &ctx.accounts.system_program.to_account_info(),
+                ctx.program_id,
+                &[USER_STATE_SEED, global_state.key().as_ref(), entry.wallet.as_ref(), &[bump]],
+            )?;
+
+            let user_state = MilestoneUnlockAccount {
+                wallet: entry.wallet,                    // Store user's main wallet address
+                ata: user_ata,                           // Store user's ATA address
+                allocated_tokens: entry.locked_tokens,   // Original allocation, never decreases
+                total_locked_tokens: entry.locked_tokens, // Store locked tokens
+                unlocked_tokens: 0,                      // Initially, no tokens unlocked
+                deferred_tokens: 0,                      // Nothing held back by the cap yet
+                last_unlocked_milestone: 0,              // No milestones processed
+            };
+            // Write through a local slice; writing to the account's own slice would shrink it
+            user_state
+                .try_serialize(&mut &mut user_state_info.try_borrow_mut_data()?[..])
+                .map_err(|_| ErrorCode::SerializationFailed)?;
     
             // Increment total user count in global state
             global_state.user_count = global_state
                 .user_count
                 .checked_add(1)
                 .ok_or(ErrorCode::ArithmeticOverflow)?; // Stop if user count exceeds `u64::MAX`
+            // The vault now owes this allocation; auto-sell may not touch it
+            global_state.outstanding_allocations = global_state
+                .outstanding_allocations
+                .checked_add(entry.locked_tokens)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
 
             // Emit the `UserRegistered` event
             emit!(UserRegistered {
@@ -201,18 +272,150 @@ pub mod hotwings {
         Ok(())
     }
 
-    pub fn add_exempt_wallet(ctx: Context<ManageExemptWallet>, wallet: Pubkey) -> Result<()> {
+    /// Replaces the milestones that have not been reached yet
+    pub fn update_milestones(ctx: Context<UpdateMilestones>, milestones: Vec<Milestone>) -> Result<()> {
         let global_state = &mut ctx.accounts.global_state;
 
-        // Ensure authority is a signer
-        require!(ctx.accounts.authority.is_signer, ErrorCode::Unauthorized);
+        // Once a multisig is configured the schedule only changes through its proposals
+        require!(global_state.multisig == Pubkey::default(), ErrorCode::MultisigRequired);
 
-         // Ensure the caller is the global authority
-        require!(
-            ctx.accounts.authority.key() == global_state.authority,
-            ErrorCode::Unauthorized
+        apply_milestones(global_state, ctx.accounts.authority.key(), milestones)
+    }
+
+    /// Updates the unlock tax rate and how it is split between burn, marketing and treasury
+    pub fn update_tax_config(
+        ctx: Context<UpdateTaxConfig>,
+        tax_bps: u16,
+        burn_share_bps: u16,
+        marketing_share_bps: u16,
+        treasury_share_bps: u16,
+        treasury_wallet: Pubkey,
+    ) -> Result<()> {
+        let global_state = &mut ctx.accounts.global_state;
+
+        // Once a multisig is configured the tax only changes through its proposals
+        require!(global_state.multisig == Pubkey::default(), ErrorCode::MultisigRequired);
+
+        apply_tax_config(
+            global_state,
+            ctx.accounts.authority.key(),
+            tax_bps,
+            burn_share_bps,
+            marketing_share_bps,
+            treasury_share_bps,
+            treasury_wallet,
+        )
+    }
+
+    /// Chooses whether the burn share of the tax is burned on-chain or sent to the burn wallet
+    pub fn update_burn_mode(ctx: Context<UpdateTaxConfig>, burn_mode: BurnMode) -> Result<()> {
+        let global_state = &mut ctx.accounts.global_state;
+
+        require!(global_state.multisig == Pubkey::default(), ErrorCode::MultisigRequired);
+
+        apply_burn_mode(global_state, ctx.accounts.authority.key(), burn_mode);
+        Ok(())
+    }
+
+    /// Moves project tokens into the program-owned vault that backs all unlocks
+    pub fn deposit_to_vault<'info>(ctx: Context<'_, '_, '_, 'info, DepositToVault<'info>>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::ArithmeticOverflow);
+
+        // Transfer hook accounts, if the mint has a hook, come in the remaining accounts
+        transfer_checked_with_hook(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.depositor_token_account.to_account_info(),
+            &ctx.accounts.token_mint.to_account_info(),
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.depositor.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            ctx.accounts.global_state.token_decimals,
+            &[],
+        )?;
+
+        ctx.accounts.vault.reload()?;
+
+        emit!(VaultDeposited {
+            depositor: ctx.accounts.depositor.key(),
+            amount,
+            vault_balance: ctx.accounts.vault.amount,
+        });
+
+        msg!(
+            "Deposited {} tokens into the vault (balance: {})",
+            amount,
+            ctx.accounts.vault.amount
         );
+        Ok(())
+    }
+
+    /// Deposits tokens into the vault that only crank rewards may spend
+    pub fn fund_crank_rewards<'info>(ctx: Context<'_, '_, '_, 'info, DepositToVault<'info>>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::ArithmeticOverflow);
+
+        // Transfer hook accounts, if the mint has a hook, come in the remaining accounts
+        transfer_checked_with_hook(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.depositor_token_account.to_account_info(),
+            &ctx.accounts.token_mint.to_account_info(),
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.depositor.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            ctx.accounts.global_state.token_decimals,
+            &[],
+        )?;
+
+        let global_state = &mut ctx.accounts.global_state;
+        global_state.crank_reward_pool = global_state.crank_reward_pool
+            .checked_add(amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        msg!("Crank reward pool funded with {} tokens (pool: {})", amount, global_state.crank_reward_pool);
+        Ok(())
+    }
+
+    /// Sets the token reward paid to whoever runs the milestone crank
+    pub fn update_crank_reward(ctx: Context<UpdateCrankReward>, crank_reward: u64) -> Result<()> {
+        let global_state = &mut ctx.accounts.global_state;
+
+        require!(crank_reward <= MAX_CRANK_REWARD, ErrorCode::InvalidCrankReward);
+
+        global_state.crank_reward = crank_reward;
+
+        msg!("Crank reward updated to: {}", crank_reward);
+        Ok(())
+    }
+
+    /// Sets the anti-whale hold cap as a raw amount, basis points of the current supply or whole tokens
+    pub fn update_hold_cap(ctx: Context<UpdateHoldCap>, hold_cap_mode: HoldCapMode, hold_cap: u64) -> Result<()> {
+        let global_state = &mut ctx.accounts.global_state;
+
+        require!(hold_cap > 0, ErrorCode::InvalidHoldCap);
+        validate_hold_cap(hold_cap_mode, hold_cap, global_state.token_decimals)?;
+
+        // Milestone caps share the mode, so a switch must keep them meaningful too
+        let milestone_count = global_state.milestone_count as usize;
+        for milestone in global_state.milestones[..milestone_count].iter().filter(|milestone| milestone.hold_cap > 0) {
+            validate_hold_cap(hold_cap_mode, milestone.hold_cap, global_state.token_decimals)?;
+        }
+
+        global_state.hold_cap_mode = hold_cap_mode;
+        global_state.hold_cap = hold_cap;
+
+        emit!(HoldCapUpdated {
+            authority: ctx.accounts.authority.key(),
+            hold_cap_mode,
+            hold_cap,
+        });
 
+        msg!("Hold cap updated to {} ({:?})", hold_cap, hold_cap_mode);
+        Ok(())
+    }
+
+    pub fn add_exempt_wallet(ctx: Context<ManageExemptWallet>, wallet: Pubkey) -> Result<()> {
+        let global_state = &mut ctx.accounts.global_state;
 
         // Ensure that exempted wallets do not exceed the limit
         require!(
@@ -240,15 +443,6 @@ pub mod hotwings {
     pub fn remove_exempt_wallet(ctx: Context<ManageExemptWallet>, wallet: Pubkey) -> Result<()> {
         let global_state = &mut ctx.accounts.global_state;
 
-        // Ensure authority is a signer
-        require!(ctx.accounts.authority.is_signer, ErrorCode::Unauthorized);
-
-        // Ensure the caller is the global authority
-        require!(
-            ctx.accounts.authority.key() == global_state.authority,
-            ErrorCode::Unauthorized
-        );
-    
         // Remove wallet from the exempt list
         let original_count = global_state.exempted_wallets.len();
         global_state.exempted_wallets.retain(|&key| key != wallet);
@@ -262,46 +456,51 @@ pub mod hotwings {
         Ok(())
     }
 
-    /// Unlocks tokens automatically when milestone conditions are met
+    /// Advances the current milestone when its conditions are met; users then `claim` their share.
+    /// Permissionless crank: the market cap comes only from on-chain state and the caller is rewarded.
     pub fn process_milestones<'info>(
         ctx: Context<'_, '_, '_, 'info, UnlockTokens<'info>>,
-        market_cap: u64,
     ) -> Result<()> {
         let global_state = &mut ctx.accounts.global_state; 
 
-        // (1) Validate the market cap
-        require!(
-            market_cap > 0 && market_cap < 10_000_000, // Arbitrarily capped at 10M
-            ErrorCode::InvalidMarketCapValue
-        );
-    
-        // Update global market cap
-        global_state.current_market_cap = market_cap;
-    
-        // Check if three-month full unlocking conditions are met
         let current_timestamp = Clock::get()?.unix_timestamp;
-        if current_timestamp >= global_state.three_month_unlock_date || market_cap >= 2_500_000 {
-            msg!("Full unlocking allowed: Three months passed or max market cap reached.");
-            global_state.unlock_complete = true;
-        }
 
-        // Ensure there are enough remaining accounts for milestone processing.
-        require!(
-            ctx.remaining_accounts.len() > 1 && ctx.remaining_accounts.len() % 2 == 0,
-            ErrorCode::AccountNotEnough
-        );
-    
         // Iterate milestones to find the applicable range
         let mut milestone_idx = global_state.current_milestone as usize;
 
         // Ensure milestone exists and is valid
         require!(
-            milestone_idx < global_state.milestones.len(),
+            milestone_idx < global_state.milestone_count as usize,
             ErrorCode::MiletoneCompleted
         );
 
-        while milestone_idx < MAX_MILESTONES
-            && market_cap >= global_state.milestones[milestone_idx].market_cap
+        // Past the three-month deadline everything unlocks whatever the market cap, so no reading is needed
+        let market_cap = if current_timestamp >= global_state.three_month_unlock_date {
+            msg!("Full unlocking allowed: Three months passed.");
+            global_state.unlock_complete = true;
+            milestone_idx = global_state.milestone_count as usize; // Jump straight to the final milestone
+            global_state.current_market_cap
+        } else {
+            // (1) Read the market cap written by the trusted update path, never from the caller.
+            // With a TWAP window configured, a single spiked reading cannot trigger a milestone.
+            let market_cap = if global_state.twap_window_seconds > 0 {
+                market_cap_twap(global_state, current_timestamp)?
+            } else {
+                global_state.current_market_cap
+            };
+            require!(market_cap > 0, ErrorCode::InvalidMarketCapValue);
+
+            let final_milestone_idx = global_state.milestone_count as usize - 1;
+            if milestone_reached(global_state, final_milestone_idx, market_cap, current_timestamp) {
+                msg!("Full unlocking allowed: max market cap reached.");
+                global_state.unlock_complete = true;
+                milestone_idx = global_state.milestone_count as usize; // Jump straight to the final milestone
+            }
+            market_cap
+        };
+
+        while milestone_idx < global_state.milestone_count as usize
+            && milestone_reached(global_state, milestone_idx, market_cap, current_timestamp)
         {
             milestone_idx += 1;
         }
@@ -313,197 +512,187 @@ pub mod hotwings {
         // Update the current milestone
         global_state.current_milestone = milestone_idx as u8;
     
-        let milestone = &global_state.milestones[milestone_idx - 1]; // Unlock tokens for this milestone
+        let milestone = global_state.milestones[milestone_idx - 1]; // Unlock tokens for this milestone
     
         msg!(
-            "Processing milestone {}: Unlock {}% (Current Market Cap: {})",
+            "Milestone {} reached: {}% claimable (Current Market Cap: {})",
             milestone_idx - 1,
             milestone.unlock_percent,
             market_cap
         );
     
-        let account_chunks: Vec<AccountInfo<'info>> = ctx.remaining_accounts.iter().cloned().collect::<Vec<AccountInfo<'info>>>();
-        for account_chunk in account_chunks.chunks(2) {
-            let mut account_iter = account_chunk.into_iter();
-            let user_pda = account_iter.next().ok_or(ErrorCode::AccountNotEnough)?;
-            let ata_account = account_iter.next().ok_or(ErrorCode::AccountNotEnough)?;
-        
+        // The vault authority PDA signs every transfer out of the vault
+        let global_state_key = global_state.key();
+        let vault_authority_bump = [global_state.vault_authority_bump];
+        let vault_authority_seeds: &[&[u8]] =
+            &[VAULT_AUTHORITY_SEED, global_state_key.as_ref(), &vault_authority_bump];
+        let vault_signer_seeds = &[vault_authority_seeds];
+
+        // Reward the keeper that drove this unlock, only out of tokens funded for rewards
+        let crank_reward = global_state.crank_reward.min(global_state.crank_reward_pool);
+        if crank_reward > 0 {
+            global_state.crank_reward_pool -= crank_reward;
+
+            transfer_checked_with_hook(
+                &ctx.accounts.token_program.to_account_info(),
+                &ctx.accounts.vault.to_account_info(),
+                &ctx.accounts.token_mint.to_account_info(),
+                &ctx.accounts.caller_token_account.to_account_info(),
+                &ctx.accounts.vault_authority.to_account_info(),
+                ctx.remaining_accounts,
+                crank_reward,
+                global_state.token_decimals,
+                vault_signer_seeds,
+            )?;
+
+            emit!(CrankRewardPaid {
+                caller: ctx.accounts.caller.key(),
+                reward: crank_reward,
+                milestone_index: global_state.current_milestone,
+            });
+
+            msg!("Crank reward of {} tokens paid to {:?}", crank_reward, ctx.accounts.caller.key());
+        }
+
+        if global_state.unlock_complete {
+            msg!("Final milestone reached or three-month unlock period expired. Auto-sell is now allowed.");
+        }
+
+        Ok(())
+    }
+
+    /// Lets a registered user pull the tokens unlocked for them so far
+    pub fn claim<'info>(ctx: Context<'_, '_, '_, 'info, Claim<'info>>) -> Result<()> {
+        let global_state_key = ctx.accounts.global_state.key();
+        let global_state = &mut ctx.accounts.global_state;
+        let user_state = &mut ctx.accounts.user_state;
+
+        require!(has_pending_unlock(global_state, user_state), ErrorCode::NothingToClaim);
+
+        let accounts = UnlockTransferAccounts {
+            vault: ctx.accounts.vault.to_account_info(),
+            vault_authority: ctx.accounts.vault_authority.to_account_info(),
+            burn_wallet: ctx.accounts.burn_wallet.to_account_info(),
+            marketing_wallet: ctx.accounts.marketing_wallet.to_account_info(),
+            treasury_wallet: ctx.accounts.treasury_wallet.as_ref().map(|wallet| wallet.to_account_info()),
+            token_mint: ctx.accounts.token_mint.to_account_info(),
+            decimals: global_state.token_decimals,
+            token_program: ctx.accounts.token_program.to_account_info(),
+            hook_accounts: ctx.remaining_accounts,
+        };
+
+        let hold_cap = effective_hold_cap(global_state, ctx.accounts.token_mint.supply)?;
+
+        unlock_user_tokens(
+            global_state,
+            global_state_key,
+            user_state,
+            &ctx.accounts.user_ata.to_account_info(),
+            &accounts,
+            hold_cap,
+        )
+    }
+
+    /// Admin path that pushes unlocked tokens to (user state PDA, ATA) pairs in remaining accounts
+    pub fn push_unlocks<'info>(ctx: Context<'_, '_, '_, 'info, PushUnlocks<'info>>) -> Result<()> {
+        let global_state_key = ctx.accounts.global_state.key();
+        let global_state = &mut ctx.accounts.global_state;
+
+        // Remaining accounts hold (user state, ATA) pairs followed by the mint's transfer hook accounts
+        let hook_account_count =
+            transfer_hook_account_count(&ctx.accounts.token_mint.to_account_info(), ctx.remaining_accounts)?;
+        let (user_accounts, hook_accounts) = ctx.remaining_accounts
+            .split_at(ctx.remaining_accounts.len().saturating_sub(hook_account_count));
+
+        // Ensure there are enough remaining accounts for milestone processing.
+        require!(
+            user_accounts.len() > 1 && user_accounts.chunks(2).all(|pair| pair.len() == 2),
+            ErrorCode::AccountNotEnough
+        );
+
+        let hold_cap = effective_hold_cap(global_state, ctx.accounts.token_mint.supply)?;
+
+        let accounts = UnlockTransferAccounts {
+            vault: ctx.accounts.vault.to_account_info(),
+            vault_authority: ctx.accounts.vault_authority.to_account_info(),
+            burn_wallet: ctx.accounts.burn_wallet.to_account_info(),
+            marketing_wallet: ctx.accounts.marketing_wallet.to_account_info(),
+            treasury_wallet: ctx.accounts.treasury_wallet.as_ref().map(|wallet| wallet.to_account_info()),
+            token_mint: ctx.accounts.token_mint.to_account_info(),
+            decimals: global_state.token_decimals,
+            token_program: ctx.accounts.token_program.to_account_info(),
+            hook_accounts,
+        };
+
+        for account_chunk in user_accounts.chunks(2) {
+            let user_pda = &account_chunk[0];
+            let ata_account = &account_chunk[1];
+
+            // Only accept user state owned by this program
+            require!(user_pda.owner == ctx.program_id, ErrorCode::InvalidUserState);
+
             // Deserialize user state
             let mut user_state: MilestoneUnlockAccount =
-                AccountDeserialize::try_deserialize(&mut &**user_pda.try_borrow_mut_data()?)
+                AccountDeserialize::try_deserialize(&mut &**user_pda.try_borrow_data()?)
                     .map_err(|_| ErrorCode::DeserializationFailed)?;
-        
-            // Calculate the unlockable tokens
-            let tokens_to_unlock = user_state
-                .total_locked_tokens
-                .checked_mul(milestone.unlock_percent as u64)
-                .ok_or(ErrorCode::ArithmeticOverflow)? // Prevent overflow when multiplying
-                .checked_div(100)
-                .ok_or(ErrorCode::ArithmeticOverflow)?; // Prevent divide-by-zero errors
-        
-            let mut unlocked_tokens = tokens_to_unlock;
-        
-            if !global_state.unlock_complete {
-                // Check for anti-whale exemptions
-                if !global_state.exempted_wallets.contains(&user_state.wallet) {
-                    let recipient_balance = get_token_balance(&ata_account)?;
-                    let max_available_unlock = if recipient_balance
-                        .checked_add(unlocked_tokens)
-                        .ok_or(ErrorCode::ArithmeticOverflow)?
-                        > MAX_HOLD_AMOUNT
-                    {
-                        MAX_HOLD_AMOUNT
-                            .checked_sub(recipient_balance)
-                            .ok_or(ErrorCode::ArithmeticOverflow)? // Prevent subtraction underflow
-                    } else {
-                        unlocked_tokens
-                    };
-                    if max_available_unlock < unlocked_tokens {
-                        msg!(
-                            "Anti-whale restriction applied: Unlock limited for user {:?}",
-                            user_state.wallet
-                        );
-                        unlocked_tokens = max_available_unlock;
-                    }
-                }
-            }
-        
-            // Validate amount for transfer
-            require!(
-                unlocked_tokens > 0,
-                ErrorCode::ArithmeticOverflow
+
+            // User state must belong to this campaign, not another mint's
+            let (expected_pda, _) = Pubkey::find_program_address(
+                &[USER_STATE_SEED, global_state_key.as_ref(), user_state.wallet.as_ref()],
+                ctx.program_id,
             );
-        
-            // Apply the tax deduction (1.5%)
-            let tax = unlocked_tokens
-                .checked_mul(15)
-                .ok_or(ErrorCode::ArithmeticOverflow)?
-                .checked_div(1000)
-                .ok_or(ErrorCode::ArithmeticOverflow)?; // Protect against divide-by-zero or large numbers
+            require!(user_pda.key == &expected_pda, ErrorCode::InvalidUserState);
 
-            let post_tax_tokens = unlocked_tokens
-                .checked_sub(tax)
-                .ok_or(ErrorCode::ArithmeticOverflow)?; // Protect against negative results (underflow)
-        
-            // Split the tax between Burn Wallet and Marketing Wallet
-            let tax_burn = tax.checked_div(2).ok_or(ErrorCode::ArithmeticOverflow)?;
-            let tax_marketing = tax.checked_sub(tax_burn).ok_or(ErrorCode::ArithmeticOverflow)?;
-        
-            // Transfer post-tax tokens to the user
-            let cpi_transfer_to_user = token::Transfer {
-                from: ctx.accounts.project_wallet.to_account_info(),
-                to: ata_account.to_account_info(),
-                authority: ctx.accounts.project_wallet_authority.to_account_info(),
-            };
-        
-            token::transfer(
-                CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_transfer_to_user),
-                post_tax_tokens,
-            )?;
-        
-            // Transfer tax to Burn Wallet
-            let cpi_transfer_to_burn = token::Transfer {
-                from: ctx.accounts.project_wallet.to_account_info(),
-                to: ctx.accounts.burn_wallet.to_account_info(),
-                authority: ctx.accounts.project_wallet_authority.to_account_info(),
-            };
-        
-            token::transfer(
-                CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_transfer_to_burn),
-                tax_burn,
-            )?;
-        
-            // Transfer tax to Marketing Wallet
-            let cpi_transfer_to_marketing = token::Transfer {
-                from: ctx.accounts.project_wallet.to_account_info(),
-                to: ctx.accounts.marketing_wallet.to_account_info(),
-                authority: ctx.accounts.project_wallet_authority.to_account_info(),
-            };
-        
-            token::transfer(
-                CpiContext::new(
-                    ctx.accounts.token_program.to_account_info(),
-                    cpi_transfer_to_marketing,
-                ),
-                tax_marketing,
-            )?;
-        
-            // Update user state
-            user_state.total_locked_tokens = user_state
-                .total_locked_tokens
-                .checked_sub(unlocked_tokens)
-                .ok_or(ErrorCode::ArithmeticOverflow)?;
-            user_state.unlocked_tokens = user_state
-                .unlocked_tokens
-                .checked_add(unlocked_tokens)
-                .ok_or(ErrorCode::ArithmeticOverflow)?;
-            user_state.last_unlocked_milestone = global_state.current_milestone;
-        
-            // Serialize updated state back into user PDA
+            // Tokens may only go to the ATA recorded at registration
+            require!(ata_account.key == &user_state.ata, ErrorCode::UserWalletNotFound);
+
+            // Users with nothing pending are skipped rather than failing the batch
+            if !has_pending_unlock(global_state, &user_state) {
+                msg!("User {:?} is up to date, skipping", user_state.wallet);
+                continue;
+            }
+
+            unlock_user_tokens(global_state, global_state_key, &mut user_state, ata_account, &accounts, hold_cap)?;
+
+            // Serialize updated state back into user PDA, through a local slice so the account keeps its length
             user_state
-                .try_serialize(&mut *user_pda.try_borrow_mut_data()?)
+                .try_serialize(&mut &mut user_pda.try_borrow_mut_data()?[..])
                 .map_err(|_| ErrorCode::SerializationFailed)?;
-        
-            // Emit milestone processed event
-            emit!(MilestoneProcessed {
-                wallet: user_state.wallet,
-                unlocked_tokens: post_tax_tokens, // Post-tax tokens unlocked
-                milestone_index: global_state.current_milestone,
-                tax,                              // Total tax amount deducted
-                burn_tax: tax_burn,               // Tax sent to Burn Wallet
-                marketing_tax: tax_marketing,     // Tax sent to Marketing Wallet
-            });
-        
-            msg!(
-                "User {:?}: Unlocked {} tokens ({} taxed, {} to Burn Wallet, {} to Marketing Wallet)",
-                user_state.wallet,
-                post_tax_tokens,
-                tax,
-                tax_burn,
-                tax_marketing
-            );
         }
 
-        if global_state.unlock_complete {
-            msg!("Final milestone reached or three-month unlock period expired. Triggering auto-sell.");
-            auto_sell_in_liquidity_pool(ctx)?;
-        }
-    
-    
         Ok(())
     }
 
-    pub fn auto_sell_in_liquidity_pool<'info>(
-        ctx: Context<'_, '_, '_, 'info, UnlockTokens<'info>>,
-    ) -> Result<()> {
-        let global_state = &ctx.accounts.global_state;
-        
-        // Ensure liquidity pool is initialized
-        if global_state.liquidity_pool == Pubkey::default() {
-            return Err(ErrorCode::LiquidityPoolNotSet.into());
-        }
-    
-        let project_wallet_balance = get_token_balance(&ctx.accounts.project_wallet.to_account_info())?;
-        let sell_amount = project_wallet_balance.checked_mul(25).unwrap().checked_div(100).unwrap(); // 25% of project wallet
-    
-        // Perform the actual swap on Raydium liquidity pool
-        let swap_instruction = raydium_swap_instruction(
-            global_state.liquidity_pool,        // Liquidity pool address
-            ctx.accounts.project_wallet.key(), // Project wallet as the source
-            sell_amount,                        // Amount to sell
-        )?;
-    
-        // Execute the swap instruction
-        solana_program::program::invoke(
-            &swap_instruction,
-            &[
-                ctx.accounts.project_wallet.to_account_info(),
-                ctx.accounts.token_program.to_account_info(),
-                ctx.accounts.project_wallet_authority.to_account_info(), // Fee payer
-            ],
-        )?;
-    
-        msg!("Successfully auto-sold 25% of project wallet tokens in liquidity pool.");
+    /// Sells 25% of the vault surplus into the Raydium pool, once, after the unlock is complete
+    pub fn auto_sell(ctx: Context<AutoSell>, minimum_amount_out: u64) -> Result<()> {
+        let global_state = &mut ctx.accounts.global_state;
+
+        require!(global_state.unlock_complete, ErrorCode::UnlockNotComplete);
+        require!(!global_state.auto_sold, ErrorCode::AutoSellAlreadyExecuted);
+        require!(global_state.liquidity_pool != Pubkey::default(), ErrorCode::LiquidityPoolNotSet);
+        require!(minimum_amount_out > 0, ErrorCode::InvalidMinimumAmountOut);
+
+        // Only the surplus is for sale; unclaimed allocations and the crank reward pool stay in the vault
+        let surplus = ctx.accounts.vault.amount
+            .saturating_sub(global_state.outstanding_allocations)
+            .saturating_sub(global_state.crank_reward_pool);
+        let sell_amount = surplus
+            .checked_mul(25)
+            .ok_or(ErrorCode::ArithmeticOverflow)?
+            / 100; // 25% of the surplus
+        require!(sell_amount > 0, ErrorCode::NothingToSell);
+
+        global_state.auto_sold = true;
+
+        auto_sell_in_liquidity_pool(ctx.accounts, sell_amount, minimum_amount_out)?;
+
+        emit!(AutoSellExecuted {
+            authority: ctx.accounts.authority.key(),
+            amount_in: sell_amount,
+            minimum_amount_out,
+        });
+
+        msg!("Successfully auto-sold 25% of the vault surplus in liquidity pool.");
         Ok(())
     }
 
@@ -511,23 +700,14 @@ pub mod hotwings {
         // Update the current market cap in the GlobalState account
         let global_state = &mut ctx.accounts.global_state;
 
-        // Ensure authority is a signer
-        require!(ctx.accounts.authority.is_signer, ErrorCode::Unauthorized);
-    
-         // Validate that the caller is the correct authority
-        require!(
-            ctx.accounts.authority.key() == global_state.authority,
-            ErrorCode::Unauthorized
-        );
-
         // (2) Validate market cap is within reasonable bounds
         require!(
-            market_cap > 0 && market_cap <= 10_000_000, // Example: Cap at 10M for safety
+            market_cap > 0 && market_cap <= MAX_MARKET_CAP, // Cap at 10M for safety
             ErrorCode::InvalidMarketCapValue
         );
     
-        // Update the market cap
-        global_state.current_market_cap = market_cap;
+        // Update the market cap and feed the TWAP
+        record_market_cap(global_state, market_cap, Clock::get()?.unix_timestamp)?;
     
         // Emit the MarketCapUpdated event
         emit!(MarketCapUpdated {
@@ -545,367 +725,1494 @@ pub mod hotwings {
         Ok(())
     } 
 
-    pub fn update_raydium_program_id(ctx: Context<UpdateRaydiumProgramId>, new_raydium_program_id: Pubkey) -> Result<()> {
+    /// Points market cap updates at a Pyth price feed and sets its freshness limits
+    pub fn update_pyth_config(
+        ctx: Context<UpdatePythConfig>,
+        price_feed: Pubkey,
+        max_price_staleness: i64,
+        max_price_confidence_bps: u16,
+    ) -> Result<()> {
         let global_state = &mut ctx.accounts.global_state;
 
-        // Ensure authority is a signer
-        require!(ctx.accounts.authority.is_signer, ErrorCode::Unauthorized);
-    
-        // Ensure only the authority can execute this instruction
-        if ctx.accounts.authority.key() != global_state.authority {
-            return Err(ErrorCode::Unauthorized.into());
-        }
-    
-        // Update the Raydium program ID
-        global_state.raydium_program_id = new_raydium_program_id;
-    
-        msg!("Raydium program ID updated to: {:?}", new_raydium_program_id);
-    
-        Ok(())
-    }
+        require!(global_state.multisig == Pubkey::default(), ErrorCode::MultisigRequired);
 
+        apply_pyth_config(
+            global_state,
+            ctx.accounts.authority.key(),
+            price_feed,
+            max_price_staleness,
+            max_price_confidence_bps,
+        )
+    }
 
-    pub fn update_liquidity_pool_address(
-        ctx: Context<UpdateLiquidityPoolAddress>,
-        new_liquidity_pool: Pubkey,
-    ) -> Result<()> {
+    /// Sets `current_market_cap` from the configured Pyth feed and the mint's supply
+    pub fn update_market_cap_from_pyth(ctx: Context<UpdateMarketCapFromPyth>) -> Result<()> {
         let global_state = &mut ctx.accounts.global_state;
+        let price = PythPrice::load(&ctx.accounts.price_feed.try_borrow_data()?)?;
 
-        // Ensure authority is a signer
-        require!(ctx.accounts.authority.is_signer, ErrorCode::Unauthorized);
+        // Reject negative, stale or low-confidence prices
+        require!(price.price > 0, ErrorCode::InvalidPriceFeed);
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now.saturating_sub(price.publish_time) <= global_state.max_price_staleness,
+            ErrorCode::StalePrice
+        );
+        require!(
+            (price.conf as u128) * (BPS_DENOMINATOR as u128)
+                <= (price.price as u128) * (global_state.max_price_confidence_bps as u128),
+            ErrorCode::PriceConfidenceTooWide
+        );
 
-        // Ensure only the authority can execute this instruction
-        if ctx.accounts.authority.key() != global_state.authority {
-            return Err(ErrorCode::Unauthorized.into());
-        }
+        let market_cap = market_cap_from_price(
+            price.price as u64,
+            price.expo,
+            ctx.accounts.token_mint.supply,
+            ctx.accounts.token_mint.decimals,
+        )?;
+        require!(
+            market_cap > 0 && market_cap <= MAX_MARKET_CAP,
+            ErrorCode::InvalidMarketCapValue
+        );
 
-        global_state.liquidity_pool = new_liquidity_pool; // Update the liquidity pool address
+        record_market_cap(global_state, market_cap, now)?;
 
-        msg!("Liquidity pool address updated to: {:?}", new_liquidity_pool);
-        Ok(())
-    }
+        emit!(MarketCapUpdated {
+            authority: ctx.accounts.caller.key(),
+            market_cap,
+        });
 
-    pub fn register_transfer_hook(ctx: Context<RegisterHook>) -> Result<()> {
-        // Define the update_transfer_hook function
-        fn update_transfer_hook(
-            token_program_id: &Pubkey,
-            mint: &Pubkey,
-            hook_program_id: Option<&Pubkey>,
-            authority: &Pubkey,
-        ) -> std::result::Result<Instruction, ProgramError> {
-            let accounts = vec![
-                AccountMeta::new(*mint, false), // Mint address (non-writable)
-                AccountMeta::new_readonly(*authority, true), // Authority (signer)
-            ];
+        msg!(
+            "Market cap updated from Pyth: {} (price {} x 10^{})",
+            market_cap,
+            price.price,
+            price.expo
+        );
+        Ok(())
+    }
+
+    /// Sets `current_market_cap` from the Raydium pool's spot price and the mint's supply
+    pub fn update_market_cap_from_pool(ctx: Context<UpdateMarketCapFromPool>) -> Result<()> {
+        let global_state = &mut ctx.accounts.global_state;
+        let pool = RaydiumAmmPool::load(&ctx.accounts.liquidity_pool.try_borrow_data()?)?;
+        let token_mint = global_state.token_mint;
+
+        // The token may sit on either side of the pool
+        let (token_vault, quote_vault, quote_mint, quote_decimals) = if pool.coin_vault_mint == token_mint {
+            (pool.coin_vault, pool.pc_vault, pool.pc_vault_mint, pool.pc_decimals)
+        } else if pool.pc_vault_mint == token_mint {
+            (pool.pc_vault, pool.coin_vault, pool.coin_vault_mint, pool.coin_decimals)
+        } else {
+            return Err(ErrorCode::InvalidLiquidityPool.into());
+        };
+
+        // Only trust the vaults the pool itself points at
+        let base_vault = &ctx.accounts.base_vault;
+        let quote_vault_account = &ctx.accounts.quote_vault;
+        require!(
+            base_vault.key() == token_vault && base_vault.mint == token_mint,
+            ErrorCode::InvalidPoolVault
+        );
+        require!(
+            quote_vault_account.key() == quote_vault && quote_vault_account.mint == quote_mint,
+            ErrorCode::InvalidPoolVault
+        );
+
+        let base_reserve = base_vault.amount;
+        let quote_reserve = quote_vault_account.amount;
+        require!(base_reserve > 0 && quote_reserve > 0, ErrorCode::InvalidMarketCapValue);
+
+        // Spot price is quote per token, so market cap = supply * quote / base, in whole quote units
+        let market_cap = (ctx.accounts.token_mint.supply as u128)
+            .checked_mul(quote_reserve as u128)
+            .ok_or(ErrorCode::ArithmeticOverflow)?
+            .checked_div(
+                10u128
+                    .checked_pow(quote_decimals as u32)
+                    .and_then(|scale| scale.checked_mul(base_reserve as u128))
+                    .ok_or(ErrorCode::ArithmeticOverflow)?,
+            )
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        let market_cap = u64::try_from(market_cap).map_err(|_| ErrorCode::InvalidMarketCapValue)?;
+        require!(
+            market_cap > 0 && market_cap <= MAX_MARKET_CAP,
+            ErrorCode::InvalidMarketCapValue
+        );
+
+        record_market_cap(global_state, market_cap, Clock::get()?.unix_timestamp)?;
+
+        emit!(MarketCapUpdated {
+            authority: ctx.accounts.caller.key(),
+            market_cap,
+        });
+
+        msg!(
+            "Market cap updated from pool reserves: {} (base {}, quote {})",
+            market_cap,
+            base_reserve,
+            quote_reserve
+        );
+        Ok(())
+    }
+
+    /// Records the current market cap as a TWAP observation, so flat periods still count
+    pub fn record_market_cap_observation(ctx: Context<RecordMarketCapObservation>) -> Result<()> {
+        let global_state = &mut ctx.accounts.global_state;
+        let market_cap = global_state.current_market_cap;
+        require!(market_cap > 0, ErrorCode::InvalidMarketCapValue);
+
+        record_market_cap(global_state, market_cap, Clock::get()?.unix_timestamp)?;
+        Ok(())
+    }
+
+    /// Returns the market cap TWAP over the configured window
+    pub fn get_market_cap_twap(ctx: Context<ReadMarketCap>) -> Result<u64> {
+        let global_state = &ctx.accounts.global_state;
+        require!(global_state.twap_window_seconds > 0, ErrorCode::InvalidTwapWindow);
+
+        let twap = market_cap_twap(global_state, Clock::get()?.unix_timestamp)?;
+        msg!("Market cap TWAP over {}s: {}", global_state.twap_window_seconds, twap);
+        Ok(twap)
+    }
+
+    /// Sets the TWAP window milestones are checked against (0 uses the spot market cap)
+    pub fn update_twap_window(ctx: Context<UpdateTwapWindow>, twap_window_seconds: i64) -> Result<()> {
+        let global_state = &mut ctx.accounts.global_state;
+
+        require!(global_state.multisig == Pubkey::default(), ErrorCode::MultisigRequired);
+
+        apply_twap_window(global_state, twap_window_seconds)
+    }
+
+    pub fn update_raydium_program_id(ctx: Context<UpdateRaydiumProgramId>, new_raydium_program_id: Pubkey) -> Result<()> {
+        let global_state = &mut ctx.accounts.global_state;
+
+        // Once a multisig is configured the program ID only changes through its proposals
+        require!(global_state.multisig == Pubkey::default(), ErrorCode::MultisigRequired);
+
+        // Update the Raydium program ID
+        global_state.raydium_program_id = new_raydium_program_id;
     
-            let data = {
-                let mut data = Vec::with_capacity(1 + 32 + 1);
-                data.push(0); // Instruction identifier for update_transfer_hook
-                data.extend_from_slice(mint.as_ref());
-                data.push(hook_program_id.is_some() as u8);
-                if let Some(hook_program_id) = hook_program_id {
-                    data.extend_from_slice(hook_program_id.as_ref());
-                }
-                data
-            };
+        msg!("Raydium program ID updated to: {:?}", new_raydium_program_id);
     
-            Ok(Instruction {
-                program_id: *token_program_id,
-                accounts,
-                data,
-            })
+        Ok(())
+    }
+
+
+    pub fn update_liquidity_pool_address(
+        ctx: Context<UpdateLiquidityPoolAddress>,
+        new_liquidity_pool: Pubkey,
+    ) -> Result<()> {
+        let global_state = &mut ctx.accounts.global_state;
+
+        // Once a multisig is configured the pool only changes through its proposals
+        require!(global_state.multisig == Pubkey::default(), ErrorCode::MultisigRequired);
+
+        global_state.liquidity_pool = new_liquidity_pool; // Update the liquidity pool address
+
+        msg!("Liquidity pool address updated to: {:?}", new_liquidity_pool);
+        Ok(())
+    }
+
+    /// Routes the Token-2022 transfer hook `Execute` instruction, which carries no Anchor discriminator
+    pub fn fallback<'info>(program_id: &Pubkey, accounts: &'info [AccountInfo<'info>], data: &[u8]) -> Result<()> {
+        match TransferHookInstruction::unpack(data)? {
+            TransferHookInstruction::Execute { amount } => on_transfer(program_id, accounts, amount),
+            _ => Err(ProgramError::InvalidInstructionData.into()),
         }
-    
-        // Program ID to set as the TransferHook handler (your program's ID)
-        let transfer_hook_program_id = crate::id();
-    
-        // Construct the instruction to update the transfer hook
-        let update_ix = update_transfer_hook(
-            &token_2022_program_id(),                     // SPL Token-2022 program
-            &ctx.accounts.token_mint.key(),              // Token-2022 mint address
-            Some(&transfer_hook_program_id),             // Program to be set as the transfer-hook handler
-            &ctx.accounts.authority.key(),               // Authority that manages the token mint
+    }
+
+    /// Creates the list of extra accounts Token-2022 passes to the transfer hook
+    pub fn initialize_extra_account_meta_list(ctx: Context<InitializeExtraAccountMetaList>) -> Result<()> {
+        let extra_account_metas = extra_account_metas(&ctx.accounts.global_state.key())?;
+        ExtraAccountMetaList::init::<ExecuteInstruction>(
+            &mut ctx.accounts.extra_account_meta_list.try_borrow_mut_data()?,
+            &extra_account_metas,
         )?;
-    
-        // Perform CPI (Cross-Program Invocation) to execute the instruction
-        invoke(
-            &update_ix,
-            &[
-                ctx.accounts.token_mint.to_account_info(),
-                ctx.accounts.token_program.to_account_info(),
-                ctx.accounts.authority.to_account_info(),
-            ],
+
+        msg!("Extra account meta list initialized: {:?}", ctx.accounts.extra_account_meta_list.key());
+        Ok(())
+    }
+
+    /// Rewrites the extra account list, e.g. after the hook starts reading new accounts
+    pub fn update_extra_account_meta_list(ctx: Context<UpdateExtraAccountMetaList>) -> Result<()> {
+        let extra_account_metas = extra_account_metas(&ctx.accounts.global_state.key())?;
+        ExtraAccountMetaList::update::<ExecuteInstruction>(
+            &mut ctx.accounts.extra_account_meta_list.try_borrow_mut_data()?,
+            &extra_account_metas,
         )?;
-    
+
+        msg!("Extra account meta list updated: {:?}", ctx.accounts.extra_account_meta_list.key());
+        Ok(())
+    }
+
+    /// Points the mint's TransferHook extension at `hook_program_id`. Vault payouts CPI into
+    /// Token-2022, which calls the hook, so the hook must be a program other than this one.
+    pub fn register_transfer_hook(ctx: Context<RegisterHook>, hook_program_id: Pubkey) -> Result<()> {
+        require!(hook_program_id != crate::id(), ErrorCode::SelfTransferHook);
+        update_transfer_hook_program(ctx.accounts, Some(hook_program_id))?;
+
         msg!("Transfer hook registered successfully!");
         Ok(())
     }
 
-    pub fn update_authority(ctx: Context<UpdateAuthority>, new_authority: Pubkey) -> Result<()> {
+    /// Sets `hook_program_id` as the hook while the mint is being created, before `InitializeMint`.
+    /// The signing authority becomes the mint's transfer hook authority.
+    pub fn initialize_transfer_hook(ctx: Context<InitializeHook>, hook_program_id: Pubkey) -> Result<()> {
+        require!(hook_program_id != crate::id(), ErrorCode::SelfTransferHook); // See `register_transfer_hook`
+
+        let initialize_ix = transfer_hook::instruction::initialize(
+            &ctx.accounts.token_program.key(),
+            &ctx.accounts.token_mint.key(),
+            Some(ctx.accounts.authority.key()),
+            Some(hook_program_id),
+        )?;
+
+        invoke(&initialize_ix, &[ctx.accounts.token_mint.to_account_info()])?;
+
+        msg!("Transfer hook initialized on mint: {:?}", ctx.accounts.token_mint.key());
+        Ok(())
+    }
+
+    /// Removes the hook from the mint, so transfers no longer call this program
+    pub fn clear_transfer_hook(ctx: Context<RegisterHook>) -> Result<()> {
+        update_transfer_hook_program(ctx.accounts, None)?;
+
+        msg!("Transfer hook cleared on mint: {:?}", ctx.accounts.token_mint.key());
+        Ok(())
+    }
+
+    /// First step of an authority transfer; the proposed key only takes over once it signs `accept_authority`
+    pub fn propose_authority(ctx: Context<UpdateAuthority>, new_authority: Pubkey) -> Result<()> {
         let global_state = &mut ctx.accounts.global_state;
-    
-        // Ensure the current authority is invoking this
-        require!(
-            ctx.accounts.authority.key() == global_state.authority,
-            ErrorCode::Unauthorized
-        );
-    
-        // Update the authority field in the global state
-        global_state.authority = new_authority;
-        msg!(
-            "The authority has been successfully updated to: {:?}",
-            new_authority
-        );
-    
+
+        // Once a multisig is configured only its proposals can nominate a new authority
+        require!(global_state.multisig == Pubkey::default(), ErrorCode::MultisigRequired);
+
+        set_pending_authority(global_state, new_authority)
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let global_state = &mut ctx.accounts.global_state;
+        let previous_authority = global_state.authority;
+
+        global_state.authority = ctx.accounts.new_authority.key();
+        global_state.pending_authority = Pubkey::default();
+
+        emit!(AuthorityTransferred {
+            previous_authority,
+            new_authority: global_state.authority,
+        });
+
+        msg!("The authority has been successfully updated to: {:?}", global_state.authority);
         Ok(())
     }
 
-}
+    pub fn cancel_authority_transfer(ctx: Context<UpdateAuthority>) -> Result<()> {
+        let global_state = &mut ctx.accounts.global_state;
+
+        // A transfer nominated by the multisig can only be withdrawn by the multisig
+        require!(global_state.multisig == Pubkey::default(), ErrorCode::MultisigRequired);
+
+        clear_pending_authority(global_state)
+    }
+
+    /// Assigns `role` to `holder`, replacing the previous holder
+    pub fn grant_role(ctx: Context<ManageRoles>, role: Role, holder: Pubkey) -> Result<()> {
+        require!(holder != Pubkey::default(), ErrorCode::InvalidRoleHolder);
+
+        ctx.accounts.roles.set_holder(role, holder);
+
+        emit!(RoleGranted {
+            authority: ctx.accounts.authority.key(),
+            role,
+            holder,
+        });
+
+        msg!("Role {:?} granted to: {:?}", role, holder);
+        Ok(())
+    }
+
+    /// Clears `role`; its instructions are unusable until it is granted again
+    pub fn revoke_role(ctx: Context<ManageRoles>, role: Role) -> Result<()> {
+        let roles = &mut ctx.accounts.roles;
+        let previous_holder = roles.holder(role);
+
+        require!(previous_holder != Pubkey::default(), ErrorCode::RoleNotAssigned);
+
+        roles.set_holder(role, Pubkey::default());
+
+        emit!(RoleRevoked {
+            authority: ctx.accounts.authority.key(),
+            role,
+            previous_holder,
+        });
+
+        msg!("Role {:?} revoked from: {:?}", role, previous_holder);
+        Ok(())
+    }
+
+    /// Hands sensitive admin changes to an M-of-N signer set; from then on they only go through proposals
+    pub fn create_multisig(ctx: Context<CreateMultisig>, signers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        validate_multisig_config(&signers, threshold)?;
+
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.global_state = ctx.accounts.global_state.key();
+        multisig.signers = signers.clone();
+        multisig.threshold = threshold;
+        multisig.proposal_count = 0;
+        multisig.bump = ctx.bumps.multisig;
+
+        ctx.accounts.global_state.multisig = multisig.key();
+
+        emit!(MultisigUpdated {
+            multisig: multisig.key(),
+            signers,
+            threshold,
+        });
+
+        msg!("Multisig created: {:?}, threshold {}", multisig.key(), threshold);
+        Ok(())
+    }
+
+    /// Queues an admin action; the proposer's approval is recorded straight away
+    pub fn propose_admin_action(ctx: Context<ProposeAdminAction>, action: AdminAction) -> Result<()> {
+        let multisig = &mut ctx.accounts.multisig;
+        let proposal = &mut ctx.accounts.proposal;
+        let proposer = ctx.accounts.proposer.key();
+
+        proposal.multisig = multisig.key();
+        proposal.index = multisig.proposal_count;
+        proposal.proposer = proposer;
+        proposal.action = action.clone();
+        proposal.approvals = vec![proposer];
+        proposal.executed = false;
+        proposal.bump = ctx.bumps.proposal;
+
+        multisig.proposal_count = multisig.proposal_count.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        emit!(AdminActionProposed {
+            proposal: proposal.key(),
+            index: proposal.index,
+            proposer,
+            action,
+        });
+
+        msg!("Admin action proposed: #{}", proposal.index);
+        Ok(())
+    }
 
-// <<<<<<<<<<<<<<< PLACE THIS OUTSIDE THE #[program] BLOCK >>>>>>>>>>>>>>>
+    pub fn approve_admin_action(ctx: Context<ApproveAdminAction>) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        let proposal = &mut ctx.accounts.proposal;
+        let signer = ctx.accounts.signer.key();
+
+        require!(!proposal.executed, ErrorCode::ProposalAlreadyExecuted);
+        require!(!proposal.approvals.contains(&signer), ErrorCode::AlreadyApproved);
+
+        // Approvals from signers removed since the proposal was made no longer count
+        proposal.approvals.retain(|approver| multisig.signers.contains(approver));
+        proposal.approvals.push(signer);
+
+        emit!(AdminActionApproved {
+            proposal: proposal.key(),
+            signer,
+            approvals: proposal.approvals.len() as u8,
+        });
+
+        msg!("Admin action #{} approved by: {:?}", proposal.index, signer);
+        Ok(())
+    }
+
+    /// Applies a proposal once enough current signers have approved it
+    pub fn execute_admin_action(ctx: Context<ExecuteAdminAction>) -> Result<()> {
+        let global_state = &mut ctx.accounts.global_state;
+        let multisig = &mut ctx.accounts.multisig;
+        let proposal = &mut ctx.accounts.proposal;
+        let multisig_key = multisig.key();
+
+        require!(!proposal.executed, ErrorCode::ProposalAlreadyExecuted);
+
+        let approvals = proposal
+            .approvals
+            .iter()
+            .filter(|approver| multisig.signers.contains(approver))
+            .count();
+        require!(approvals >= multisig.threshold as usize, ErrorCode::ThresholdNotMet);
+
+        // Same validation as the single-key instructions these replace
+        match proposal.action.clone() {
+            AdminAction::ProposeAuthority { new_authority } => set_pending_authority(global_state, new_authority)?,
+            AdminAction::CancelAuthorityTransfer => clear_pending_authority(global_state)?,
+            AdminAction::UpdateLiquidityPool { liquidity_pool } => {
+                global_state.liquidity_pool = liquidity_pool;
+                msg!("Liquidity pool address updated to: {:?}", liquidity_pool);
+            }
+            AdminAction::UpdateRaydiumProgramId { raydium_program_id } => {
+                global_state.raydium_program_id = raydium_program_id;
+                msg!("Raydium program ID updated to: {:?}", raydium_program_id);
+            }
+            AdminAction::UpdateTaxConfig {
+                tax_bps,
+                burn_share_bps,
+                marketing_share_bps,
+                treasury_share_bps,
+                treasury_wallet,
+            } => apply_tax_config(
+                global_state,
+                multisig_key,
+                tax_bps,
+                burn_share_bps,
+                marketing_share_bps,
+                treasury_share_bps,
+                treasury_wallet,
+            )?,
+            AdminAction::UpdateMilestones { milestones } => apply_milestones(global_state, multisig_key, milestones)?,
+            AdminAction::UpdateBurnMode { burn_mode } => apply_burn_mode(global_state, multisig_key, burn_mode),
+            AdminAction::UpdatePythConfig {
+                price_feed,
+                max_price_staleness,
+                max_price_confidence_bps,
+            } => apply_pyth_config(
+                global_state,
+                multisig_key,
+                price_feed,
+                max_price_staleness,
+                max_price_confidence_bps,
+            )?,
+            AdminAction::UpdateTwapWindow { twap_window_seconds } => apply_twap_window(global_state, twap_window_seconds)?,
+            AdminAction::UpdateMultisig { signers, threshold } => {
+                validate_multisig_config(&signers, threshold)?;
+                multisig.signers = signers.clone();
+                multisig.threshold = threshold;
+
+                emit!(MultisigUpdated {
+                    multisig: multisig_key,
+                    signers,
+                    threshold,
+                });
+            }
+        }
 
-/// Implement the `on_transfer` hook as a separate function.
-/// Token-2022 will invoke it automatically on token transfer.
-/// This function is NOT an Anchor instruction.
-/// Function that serves as the on-transfer hook
+        proposal.executed = true;
+
+        emit!(AdminActionExecuted {
+            proposal: proposal.key(),
+            executor: ctx.accounts.executor.key(),
+            action: proposal.action.clone(),
+        });
+
+        msg!("Admin action #{} executed", proposal.index);
+        Ok(())
+    }
+
+}
+
+/// Transfer hook `Execute` handler, dispatched from the program's `fallback`.
+/// Token-2022 calls it after moving the tokens, with accounts at the interface's fixed positions:
+/// 0 source, 1 mint, 2 destination, 3 owner, 4 extra account meta list, then the extra accounts
+/// from `extra_account_metas` (5 global state, 6 the source owner's user state).
+///
+/// Token-2022 hands every account over read-only and without signer privileges, so the hook can
+/// only accept or reject the transfer. It cannot redirect tokens (a transfer back into Token-2022
+/// needs the owner's signature and re-enters the program) or create user accounts on the fly.
 pub fn on_transfer<'a>(
     program_id: &Pubkey,
-    accounts: &'a [AccountInfo<'a>], // Account list: source, destination, global state, etc.
-    amount: u64,                     // Number of tokens being transferred
+    accounts: &'a [AccountInfo<'a>],
+    amount: u64, // Number of tokens being transferred
 ) -> Result<()> {
+    let [source_account, mint_account, destination_account, owner_account, extra_account_meta_list, global_state_account, ..] =
+        accounts
+    else {
+        return err!(ErrorCode::AccountNotEnough);
+    };
 
-    // (1) Validate amount to avoid overflows or invalid values
-    require!(amount > 0, ErrorCode::ArithmeticOverflow);
+    // Token-2022 flags the source while the hook runs, so nobody can call the hook directly
+    require!(is_transferring(source_account)?, ErrorCode::NotTransferring);
 
-    // Dynamically find critical accounts
-    let source_account = find_account(accounts, |acc: &AccountInfo| {
-        // Source: must belong to a user and hold tokens
-        is_token_account(acc) // Validate it's a token account
-    })?;
+    require!(
+        extra_account_meta_list.key == &get_extra_account_metas_address(mint_account.key, program_id),
+        ErrorCode::InvalidExtraAccountMetaList
+    );
 
-    let destination_account = find_account(accounts, |acc| {
-        // Destination: must belong to user and hold tokens
-        is_token_account(acc) // Validate it's a token account
-    })?;
+    // Deserialize the global state
+    require!(global_state_account.owner == program_id, ErrorCode::AccountNotFound);
+    let global_state = GlobalState::try_deserialize(&mut &global_state_account.try_borrow_data()?[..])
+        .map_err(|_| ErrorCode::DeserializationFailed)?;
+    require!(mint_account.key == &global_state.token_mint, ErrorCode::AccountNotFound);
+    // Only the mint's own config may drive its hook
+    let expected_global_state = Pubkey::create_program_address(
+        &[GLOBAL_STATE_SEED, mint_account.key.as_ref(), &[global_state.bump]],
+        program_id,
+    )
+    .map_err(|_| ErrorCode::AccountNotFound)?;
+    require!(global_state_account.key == &expected_global_state, ErrorCode::AccountNotFound);
 
-    let mint_account = find_account(accounts, |acc| {
-        // Mint: must match the token mint in transfer
-        is_mint_account(acc)
-    })?;
+    msg!(
+        "Transferring tokens: Source: {:?}, Destination: {:?}, Owner: {:?}, Amount: {:?}",
+        source_account.key, destination_account.key, owner_account.key, amount
+    );
 
-    let global_state_account = find_account(accounts, |acc| {
-        // Global state: must match the program's global state
-        acc.owner == program_id
-    })?;
+    // Anti-whale: buyers on the open market are held to the same cap as unlocks
+    check_hold_cap(&global_state, destination_account, mint_account)
+}
 
-    // Deserialize the global state
-    let global_state: GlobalState = GlobalState::try_from_slice(&global_state_account.data.borrow())
+/// Updates the program in the mint's TransferHook extension, `None` clears it
+fn update_transfer_hook_program(accounts: &RegisterHook, hook_program_id: Option<Pubkey>) -> Result<()> {
+    let update_ix = transfer_hook::instruction::update(
+        &accounts.token_program.key(),
+        &accounts.token_mint.key(),
+        &accounts.authority.key(),
+        &[],
+        hook_program_id,
+    )?;
+
+    invoke(
+        &update_ix,
+        &[accounts.token_mint.to_account_info(), accounts.authority.to_account_info()],
+    )?;
+    Ok(())
+}
+
+/// Extra accounts Token-2022 resolves and appends to every `Execute` call, in order
+fn extra_account_metas(global_state: &Pubkey) -> Result<Vec<ExtraAccountMeta>> {
+    Ok(vec![
+        ExtraAccountMeta::new_with_pubkey(global_state, false, false)?,
+        // User state PDA of the source token account's owner (bytes 32..64 of the account)
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal { bytes: USER_STATE_SEED.to_vec() },
+                Seed::AccountKey { index: 5 }, // Global state, resolved just above
+                Seed::AccountData { account_index: 0, data_index: 32, length: 32 },
+            ],
+            false,
+            false,
+        )?,
+    ])
+}
+
+/// Whether Token-2022 is in the middle of a transfer out of this account
+fn is_transferring(token_account: &AccountInfo) -> Result<bool> {
+    let data = token_account.try_borrow_data()?;
+    let account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)
         .map_err(|_| ProgramError::InvalidAccountData)?;
+    let extension = account
+        .get_extension::<TransferHookAccount>()
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    Ok(bool::from(extension.transferring))
+}
 
-    
-    let project_wallet_account = find_account(accounts, |acc| {
-        // Project wallet: must match the one in global state
-        acc.key == &global_state.project_wallet
-    })?;
-    
-    // Security: Validate project wallet against global state
+/// Rejects a transfer that leaves the destination above the anti-whale hold cap.
+/// Runs after Token-2022 moved the tokens, so the destination balance already includes them.
+/// Exempted owners, the vault, the burn wallet and liquidity pool vaults are never capped.
+/// Once the unlock is complete the cap no longer applies, as in `unlock_user_tokens`.
+pub fn check_hold_cap(
+    global_state: &GlobalState,
+    destination_account: &AccountInfo,
+    mint_account: &AccountInfo,
+) -> Result<()> {
+    if global_state.unlock_complete {
+        return Ok(());
+    }
+
+    let destination_data = destination_account.try_borrow_data()?;
+    let destination = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&destination_data)
+        .map_err(|_| ProgramError::InvalidAccountData)?
+        .base;
+
+    // The Raydium AMM v4 authority PDA owns the vaults of every pool it runs
+    let (amm_authority, _bump) =
+        Pubkey::find_program_address(&[RAYDIUM_AMM_AUTHORITY_SEED], &global_state.raydium_program_id);
+
+    // Project-controlled wallets receive tax and treasury flows that can exceed any hold cap
+    let project_wallets = [
+        global_state.burn_wallet,
+        global_state.marketing_wallet,
+        global_state.project_wallet,
+        global_state.liquidity_pool,
+        amm_authority,
+    ];
+    let whitelisted = destination_account.key == &global_state.vault
+        || project_wallets.contains(&destination.owner)
+        || (global_state.treasury_wallet != Pubkey::default() && destination.owner == global_state.treasury_wallet)
+        || global_state.exempted_wallets.contains(&destination.owner);
+    if whitelisted {
+        msg!("Destination owner {:?} is exempt from the hold cap", destination.owner);
+        return Ok(());
+    }
+
+    let supply = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_account.try_borrow_data()?)
+        .map_err(|_| ProgramError::InvalidAccountData)?
+        .base
+        .supply;
+    let hold_cap = effective_hold_cap(global_state, supply)?;
+
+    require!(destination.amount <= hold_cap, ErrorCode::HoldCapExceeded);
+    Ok(())
+}
+
+
+/// A hold cap must make sense in its mode
+fn validate_hold_cap(hold_cap_mode: HoldCapMode, hold_cap: u64, decimals: u8) -> Result<()> {
+    match hold_cap_mode {
+        HoldCapMode::Absolute => {}
+        HoldCapMode::SupplyBps => require!(hold_cap <= BPS_DENOMINATOR, ErrorCode::InvalidHoldCap),
+        // Must still fit in raw units once scaled by the decimals
+        HoldCapMode::UiAmount => {
+            ui_to_raw_amount(hold_cap, decimals).map_err(|_| ErrorCode::InvalidHoldCap)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Validates a milestone schedule before it is stored in the global state
+fn validate_milestones(milestones: &[Milestone], hold_cap_mode: HoldCapMode, decimals: u8) -> Result<()> {
     require!(
-        *project_wallet_account.key == global_state.project_wallet,
-        ErrorCode::Unauthorized
+        !milestones.is_empty() && milestones.len() <= MAX_MILESTONES,
+        ErrorCode::InvalidMilestoneSchedule
     );
-    // Ensure that all accounts are correctly identified
-    msg!("Source Account: {:?}", source_account.key);
-    msg!("Destination Account: {:?}", destination_account.key);
-    msg!("Mint Account: {:?}", mint_account.key);
-    msg!("Global State Account: {:?}", global_state_account.key);
-    msg!("Project Wallet Account: {:?}", project_wallet_account.key);
-
-    // Validate balance of source account
-    let source_balance = get_token_balance(source_account)?;
+
+    for pair in milestones.windows(2) {
+        // Thresholds must be strictly increasing, percentages must never go down
+        require!(
+            pair[1].market_cap > pair[0].market_cap
+                && pair[1].unlock_percent >= pair[0].unlock_percent,
+            ErrorCode::InvalidMilestoneSchedule
+        );
+    }
+
+    // Hold caps may only relax as milestones advance
+    let mut hold_cap = 0;
+    for milestone in milestones.iter().filter(|milestone| milestone.hold_cap > 0) {
+        require!(milestone.hold_cap >= hold_cap, ErrorCode::InvalidMilestoneSchedule);
+        validate_hold_cap(hold_cap_mode, milestone.hold_cap, decimals)?;
+        hold_cap = milestone.hold_cap;
+    }
+
     require!(
-        source_balance >= amount,
-        ErrorCode::TokenTransferFailed
+        milestones[0].market_cap > 0 && milestones[milestones.len() - 1].unlock_percent == 100,
+        ErrorCode::InvalidMilestoneSchedule
     );
 
-    msg!(
-        "Transferring tokens: Source: {:?}, Destination: {:?}, Amount: {:?}",
-        source_account.key, destination_account.key, amount
+    Ok(())
+}
+
+/// Copies a validated schedule into the fixed-size milestone array
+fn set_milestones(global_state: &mut GlobalState, milestones: &[Milestone]) {
+    global_state.milestones = [Milestone::default(); MAX_MILESTONES];
+    global_state.milestones[..milestones.len()].copy_from_slice(milestones);
+    global_state.milestone_count = milestones.len() as u8;
+
+    // Thresholds may have moved, so pending sustain timers start over
+    for crossed_at in global_state.milestone_crossed_at[global_state.current_milestone as usize..].iter_mut() {
+        *crossed_at = 0;
+    }
+}
+
+/// Replaces the milestones that have not been reached yet
+fn apply_milestones(global_state: &mut GlobalState, authority: Pubkey, milestones: Vec<Milestone>) -> Result<()> {
+    let reached = global_state.current_milestone as usize;
+
+    // Nothing left to edit once every milestone has been reached
+    require!(
+        reached < global_state.milestone_count as usize,
+        ErrorCode::MiletoneCompleted
     );
-  
 
-    // Check if the source wallet is exempted (e.g., Project Wallet)
-    if global_state.exempted_wallets.contains(&source_account.key) {
-        msg!("Transfer from exempted account {:?}, skipping transfer hook", source_account.key);
-        return Ok(());
+    validate_milestones(&milestones, global_state.hold_cap_mode, global_state.token_decimals)?;
+
+    // Reached milestones are history and must be carried over unchanged
+    require!(milestones.len() > reached, ErrorCode::MilestoneAlreadyReached);
+    for (index, milestone) in milestones.iter().take(reached).enumerate() {
+        let existing = &global_state.milestones[index];
+        require!(
+            milestone.market_cap == existing.market_cap
+                && milestone.unlock_percent == existing.unlock_percent
+                && milestone.sustain_seconds == existing.sustain_seconds
+                && milestone.hold_cap == existing.hold_cap,
+            ErrorCode::MilestoneAlreadyReached
+        );
     }
- 
-    // (3) Detect source program for AMM/Raydium transactions
-    if RaydiumTransactionHelper::is_raydium_transaction(
-        source_account,
-        global_state.raydium_program_id,
-    ) {
-        msg!("Raydium transaction detected. Redirecting tokens to project wallet.");
 
-        
-        // Transfer all tokens to the Project Wallet
-        let transfer_ix = spl_token_2022::instruction::transfer_checked(
-            &spl_token_2022::id(),
-            source_account.key,
-            &mint_account.key(),
-            project_wallet_account.key,
-            source_account.key, // Must be signed by source authority
-            &[],
-            amount,
-            9, // Decimal places (change if your token has decimals)
+    set_milestones(global_state, &milestones);
+
+    emit!(MilestonesUpdated {
+        authority,
+        milestones,
+    });
+
+    msg!("Milestone schedule updated: {} milestones", global_state.milestone_count);
+    Ok(())
+}
+
+/// Updates the unlock tax rate and how it is split between burn, marketing and treasury
+fn apply_tax_config(
+    global_state: &mut GlobalState,
+    authority: Pubkey,
+    tax_bps: u16,
+    burn_share_bps: u16,
+    marketing_share_bps: u16,
+    treasury_share_bps: u16,
+    treasury_wallet: Pubkey,
+) -> Result<()> {
+    require!(tax_bps <= MAX_TAX_BPS, ErrorCode::InvalidTaxConfig);
+
+    // The shares split the whole tax, so they must add up to exactly 100%
+    let total_share = burn_share_bps as u64 + marketing_share_bps as u64 + treasury_share_bps as u64;
+    require!(total_share == BPS_DENOMINATOR, ErrorCode::InvalidTaxConfig);
+
+    // A treasury share needs somewhere to go
+    require!(
+        treasury_share_bps == 0 || treasury_wallet != Pubkey::default(),
+        ErrorCode::InvalidTreasuryWallet
+    );
+
+    global_state.tax_bps = tax_bps;
+    global_state.burn_share_bps = burn_share_bps;
+    global_state.marketing_share_bps = marketing_share_bps;
+    global_state.treasury_share_bps = treasury_share_bps;
+    global_state.treasury_wallet = treasury_wallet;
+
+    emit!(TaxConfigUpdated {
+        authority,
+        tax_bps,
+        burn_share_bps,
+        marketing_share_bps,
+        treasury_share_bps,
+        treasury_wallet,
+    });
+
+    msg!(
+        "Tax config updated: {} bps (burn {} / marketing {} / treasury {})",
+        tax_bps,
+        burn_share_bps,
+        marketing_share_bps,
+        treasury_share_bps
+    );
+    Ok(())
+}
+
+/// Switches how the burn share of the tax is handled
+fn apply_burn_mode(global_state: &mut GlobalState, authority: Pubkey, burn_mode: BurnMode) {
+    global_state.burn_mode = burn_mode;
+
+    emit!(BurnModeUpdated {
+        authority,
+        burn_mode,
+        total_burned: global_state.total_burned,
+    });
+
+    msg!("Burn mode updated to: {:?}", burn_mode);
+}
+
+/// Points market cap updates at a Pyth feed and sets how fresh and precise its price must be
+fn apply_pyth_config(
+    global_state: &mut GlobalState,
+    authority: Pubkey,
+    price_feed: Pubkey,
+    max_price_staleness: i64,
+    max_price_confidence_bps: u16,
+) -> Result<()> {
+    require!(max_price_staleness > 0, ErrorCode::InvalidPriceFeed);
+    require!(
+        max_price_confidence_bps as u64 <= BPS_DENOMINATOR,
+        ErrorCode::InvalidPriceFeed
+    );
+
+    global_state.pyth_price_feed = price_feed;
+    global_state.max_price_staleness = max_price_staleness;
+    global_state.max_price_confidence_bps = max_price_confidence_bps;
+
+    emit!(PythConfigUpdated {
+        authority,
+        price_feed,
+        max_price_staleness,
+        max_price_confidence_bps,
+    });
+
+    msg!("Pyth price feed updated to: {:?}", price_feed);
+    Ok(())
+}
+
+/// Sets the window pool readings are averaged over, 0 = spot price
+fn apply_twap_window(global_state: &mut GlobalState, twap_window_seconds: i64) -> Result<()> {
+    require!(
+        (0..=MAX_TWAP_WINDOW_SECONDS).contains(&twap_window_seconds),
+        ErrorCode::InvalidTwapWindow
+    );
+
+    global_state.twap_window_seconds = twap_window_seconds;
+
+    msg!("TWAP window updated to: {}s", twap_window_seconds);
+    Ok(())
+}
+
+/// Signers must be unique and the threshold reachable
+fn validate_multisig_config(signers: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(
+        !signers.is_empty() && signers.len() <= MAX_MULTISIG_SIGNERS,
+        ErrorCode::InvalidMultisigConfig
+    );
+    require!(
+        threshold > 0 && threshold as usize <= signers.len(),
+        ErrorCode::InvalidMultisigConfig
+    );
+
+    for (index, signer) in signers.iter().enumerate() {
+        require!(
+            *signer != Pubkey::default() && !signers[index + 1..].contains(signer),
+            ErrorCode::InvalidMultisigConfig
         );
+    }
 
-        solana_program::program::invoke(
-            &transfer_ix?,
-            &[
-                source_account.clone(),
-                project_wallet_account.clone(),
-                mint_account.clone(),
-            ],
-        )?;
+    Ok(())
+}
 
-        // Log post-transfer
-        msg!("Tokens redirected to project wallet.");
+/// First step of an authority transfer; the proposed key only takes over once it signs `accept_authority`
+fn set_pending_authority(global_state: &mut GlobalState, new_authority: Pubkey) -> Result<()> {
+    require!(
+        new_authority != Pubkey::default() && new_authority != global_state.authority,
+        ErrorCode::InvalidPendingAuthority
+    );
 
-        // (2) Automatically Register User
-        msg!("Automatically registering user from Raydium transaction: {:?}", source_account.key);
+    // Replaces any earlier proposal
+    global_state.pending_authority = new_authority;
 
-        // Retrieve or initialize the user
-        let user_state_account = find_account(accounts, |acc| {
-            // User state account must be derived based on the user's key
-            let (pda, _bump) = Pubkey::find_program_address(
-                &[b"user_state", source_account.key.as_ref()], // Seed for the PDA
-                program_id,
-            );
-            acc.key == &pda
-        }).ok();
+    emit!(AuthorityProposed {
+        authority: global_state.authority,
+        pending_authority: new_authority,
+    });
 
-        // Call the logic for registering a user (using your existing function)
-        register_user_internal(
-            program_id,
-            source_account.key,      // User's wallet
-            global_state_account,    // Global state
-            user_state_account,      // User state PDA (if it exists)
-            amount,                  // Amount transferred
-            project_wallet_account,  // Reference to the project wallet
-        )?;
+    msg!("Authority transfer proposed to: {:?}", new_authority);
+    Ok(())
+}
 
-        msg!("User successfully registered: {:?}", source_account.key);
-    } else {
-        msg!("Normal transfer logic executed.");
+/// Drops a proposed authority transfer before it is accepted
+fn clear_pending_authority(global_state: &mut GlobalState) -> Result<()> {
+    let pending_authority = global_state.pending_authority;
+
+    require!(pending_authority != Pubkey::default(), ErrorCode::NoPendingAuthority);
+
+    global_state.pending_authority = Pubkey::default();
+
+    emit!(AuthorityTransferCancelled {
+        authority: global_state.authority,
+        pending_authority,
+    });
+
+    msg!("Authority transfer to {:?} cancelled", pending_authority);
+    Ok(())
+}
+
+/// Starts or resets the sustain timer of every pending milestone for a new reading
+fn track_milestone_crossings(global_state: &mut GlobalState, market_cap: u64, now: i64) {
+    let pending = global_state.current_milestone as usize..global_state.milestone_count as usize;
+    for index in pending {
+        if market_cap < global_state.milestones[index].market_cap {
+            global_state.milestone_crossed_at[index] = 0; // Dipped below, the timer resets
+        } else if global_state.milestone_crossed_at[index] == 0 {
+            global_state.milestone_crossed_at[index] = now; // First reading above the threshold
+        }
+    }
+}
+
+/// A milestone is reached once the market cap is above its threshold and has stayed there
+/// for its sustain period
+fn milestone_reached(global_state: &GlobalState, index: usize, market_cap: u64, now: i64) -> bool {
+    let milestone = &global_state.milestones[index];
+    if market_cap < milestone.market_cap {
+        return false;
+    }
+    if milestone.sustain_seconds == 0 {
+        return true;
+    }
+
+    let crossed_at = global_state.milestone_crossed_at[index];
+    crossed_at != 0 && now.saturating_sub(crossed_at) >= milestone.sustain_seconds as i64
+}
+
+/// True when a milestone was reached since the user's last unlock or earlier tokens are still deferred
+fn has_pending_unlock(global_state: &GlobalState, user_state: &MilestoneUnlockAccount) -> bool {
+    user_state.last_unlocked_milestone < global_state.current_milestone || user_state.deferred_tokens > 0
+}
+
+/// Anti-whale hold cap in raw token units for the given mint supply.
+/// The latest reached milestone with its own cap relaxes the base cap, never tightens it.
+pub fn effective_hold_cap(global_state: &GlobalState, supply: u64) -> Result<u64> {
+    let milestone_cap = global_state.milestones[..global_state.current_milestone as usize]
+        .iter()
+        .rev()
+        .map(|milestone| milestone.hold_cap)
+        .find(|hold_cap| *hold_cap > 0)
+        .unwrap_or(0);
+    let hold_cap = global_state.hold_cap.max(milestone_cap);
+
+    match global_state.hold_cap_mode {
+        HoldCapMode::Absolute => Ok(hold_cap),
+        HoldCapMode::SupplyBps => {
+            let cap = (supply as u128)
+                .checked_mul(hold_cap as u128)
+                .ok_or(ErrorCode::ArithmeticOverflow)?
+                / BPS_DENOMINATOR as u128;
+            Ok(u64::try_from(cap).unwrap_or(u64::MAX))
+        }
+        HoldCapMode::UiAmount => ui_to_raw_amount(hold_cap, global_state.token_decimals),
     }
+}
+
+/// Converts whole tokens to base units for a mint with `decimals`
+pub fn ui_to_raw_amount(amount: u64, decimals: u8) -> Result<u64> {
+    let scale = 10u64.checked_pow(decimals as u32).ok_or(ErrorCode::ArithmeticOverflow)?;
+    Ok(amount.checked_mul(scale).ok_or(ErrorCode::ArithmeticOverflow)?)
+}
+
+/// Tokens a user is owed at a cumulative unlock percentage, less what they already unlocked
+fn unlock_entitlement(user_state: &MilestoneUnlockAccount, unlock_percent: u8) -> Result<u64> {
+    let entitled = (user_state.allocated_tokens as u128)
+        .checked_mul(unlock_percent as u128)
+        .ok_or(ErrorCode::ArithmeticOverflow)?
+        / 100;
+
+    Ok((entitled as u64).saturating_sub(user_state.unlocked_tokens))
+}
 
+/// Accounts needed to move unlocked tokens and tax out of the vault
+struct UnlockTransferAccounts<'a, 'info> {
+    vault: AccountInfo<'info>,
+    vault_authority: AccountInfo<'info>,
+    burn_wallet: AccountInfo<'info>,
+    marketing_wallet: AccountInfo<'info>,
+    treasury_wallet: Option<AccountInfo<'info>>,
+    token_mint: AccountInfo<'info>,
+    decimals: u8, // Mint decimals for checked transfers
+    token_program: AccountInfo<'info>,
+    hook_accounts: &'a [AccountInfo<'info>], // Transfer hook accounts, empty for mints without a hook
+}
+
+/// `transfer_checked` that also passes the mint's transfer hook accounts, found by key in `hook_accounts`
+#[allow(clippy::too_many_arguments)]
+fn transfer_checked_with_hook<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    hook_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    decimals: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    // Token-2022 would call back into this program, which the runtime rejects as reentrancy
+    require!(transfer_hook_program_id(mint)? != Some(crate::id()), ErrorCode::SelfTransferHook);
+
+    spl_token_2022::onchain::invoke_transfer_checked(
+        token_program.key,
+        from.clone(),
+        mint.clone(),
+        to.clone(),
+        authority.clone(),
+        hook_accounts,
+        amount,
+        decimals,
+        signer_seeds,
+    )?;
     Ok(())
 }
 
-fn register_user_internal(
-    program_id: &Pubkey,
-    user_wallet: &Pubkey,
-    global_state_account: &AccountInfo,
-    user_state_account: Option<&AccountInfo>, // User state PDA (if it exists)
-    locked_amount: u64,                      // The amount of tokens locked
-    project_wallet_account: &AccountInfo,
+/// Transfer out of the vault, signed by the vault authority PDA
+fn vault_transfer<'info>(
+    accounts: &UnlockTransferAccounts<'_, 'info>,
+    to: &AccountInfo<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
 ) -> Result<()> {
-    // Deserialize Global State
-    let global_state: GlobalState = GlobalState::try_from_slice(&global_state_account.data.borrow())
-        .map_err(|_| ErrorCode::DeserializationFailed)?;
+    transfer_checked_with_hook(
+        &accounts.token_program,
+        &accounts.vault,
+        &accounts.token_mint,
+        to,
+        &accounts.vault_authority,
+        accounts.hook_accounts,
+        amount,
+        accounts.decimals,
+        signer_seeds,
+    )
+}
 
-    // If the PDA exists, update the existing user state
-    if let Some(user_pda) = user_state_account {
-        // Deserialize the user state
-        let mut user_state: MilestoneUnlockAccount = MilestoneUnlockAccount::try_from_slice(&user_pda.data.borrow())
-            .map_err(|_| ErrorCode::DeserializationFailed)?;
+/// Number of trailing accounts carrying the mint's transfer hook: its validation account,
+/// the hook program and every extra account the validation account lists
+fn transfer_hook_account_count(token_mint: &AccountInfo, remaining_accounts: &[AccountInfo]) -> Result<usize> {
+    let Some(hook_program_id) = transfer_hook_program_id(token_mint)? else {
+        return Ok(0);
+    };
 
-        // Update the locked tokens
-        user_state.total_locked_tokens = user_state
-            .total_locked_tokens
-            .checked_add(locked_amount)
-            .ok_or(ErrorCode::ArithmeticOverflow)?;
+    let validation_address = get_extra_account_metas_address(token_mint.key, &hook_program_id);
+    let validation_account = remaining_accounts
+        .iter()
+        .find(|account| account.key == &validation_address)
+        .ok_or(ErrorCode::InvalidExtraAccountMetaList)?;
+    let validation_data = validation_account.try_borrow_data()?;
+    let extra_account_count = ExtraAccountMetaList::unpack_with_tlv_state::<ExecuteInstruction>(
+        &TlvStateBorrowed::unpack(&validation_data)?,
+    )?
+    .data()
+    .len();
+
+    Ok(extra_account_count + 2)
+}
 
-        // Serialize the updated User PDA
-        user_state.serialize(&mut *user_pda.data.borrow_mut())?;
-        msg!("User PDA updated for wallet: {:?} with locked tokens: {}", user_wallet, locked_amount);
-    } else {
-        // If the PDA doesn't exist, create it and initialize the user
-        let (user_pda, _bump) = Pubkey::find_program_address(
-            &[b"user_state", user_wallet.as_ref()],
-            program_id,
-        );
+/// Program set in the mint's TransferHook extension, `None` for mints without a hook
+fn transfer_hook_program_id(token_mint: &AccountInfo) -> Result<Option<Pubkey>> {
+    let mint_data = token_mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    Ok(transfer_hook::get_program_id(&mint))
+}
 
-        let lamports = Rent::get()?.minimum_balance(MilestoneUnlockAccount::LEN);
+/// Unlocks a user's share for the current milestone, applying the anti-whale cap and tax.
+/// Shared by the user-signed `claim` and the admin `push_unlocks` paths.
+fn unlock_user_tokens<'info>(
+    global_state: &mut GlobalState,
+    global_state_key: Pubkey,
+    user_state: &mut MilestoneUnlockAccount,
+    user_ata: &AccountInfo<'info>,
+    accounts: &UnlockTransferAccounts<'_, 'info>,
+    hold_cap: u64,
+) -> Result<()> {
+    require!(global_state.current_milestone > 0, ErrorCode::MiletoneNotReached);
+    let milestone = global_state.milestones[global_state.current_milestone as usize - 1];
+
+    // The vault authority PDA signs every transfer out of the vault
+    let vault_authority_bump = [global_state.vault_authority_bump];
+    let vault_authority_seeds: &[&[u8]] =
+        &[VAULT_AUTHORITY_SEED, global_state_key.as_ref(), &vault_authority_bump];
+    let vault_signer_seeds = &[vault_authority_seeds];
+
+    // Milestone percentages are cumulative, so anything skipped since the last unlock is included
+    let tokens_to_unlock = unlock_entitlement(user_state, milestone.unlock_percent)?;
+    if tokens_to_unlock == 0 {
+        // Consecutive milestones may share a percentage
+        msg!("User {:?}: nothing new unlocked at this milestone", user_state.wallet);
+        user_state.last_unlocked_milestone = global_state.current_milestone;
+        return Ok(());
+    }
 
-        let create_instruction = solana_program::system_instruction::create_account(
-            project_wallet_account.key,
-            &user_pda,
-            lamports,
-            MilestoneUnlockAccount::LEN as u64,
-            program_id,
+    let mut unlocked_tokens = tokens_to_unlock;
+
+    if !global_state.unlock_complete {
+        // Check for anti-whale exemptions
+        if !global_state.exempted_wallets.contains(&user_state.wallet) {
+            let recipient_balance = get_token_balance(user_ata)?;
+            let max_available_unlock = if recipient_balance
+                .checked_add(unlocked_tokens)
+                .ok_or(ErrorCode::ArithmeticOverflow)?
+                > hold_cap
+            {
+                hold_cap.saturating_sub(recipient_balance) // Nothing if already at or above the cap
+            } else {
+                unlocked_tokens
+            };
+            if max_available_unlock < unlocked_tokens {
+                msg!(
+                    "Anti-whale restriction applied: Unlock limited for user {:?}",
+                    user_state.wallet
+                );
+                unlocked_tokens = max_available_unlock;
+            }
+        }
+    }
+
+    // Whatever the cap held back stays owed; previously deferred tokens are paid out first
+    let previously_deferred = user_state.deferred_tokens;
+    user_state.deferred_tokens = tokens_to_unlock
+        .checked_sub(unlocked_tokens)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    let released_tokens = previously_deferred.min(unlocked_tokens);
+
+    if user_state.deferred_tokens > previously_deferred.saturating_sub(released_tokens) {
+        emit!(UnlockDeferred {
+            wallet: user_state.wallet,
+            milestone_index: global_state.current_milestone,
+            deferred_tokens: user_state.deferred_tokens,
+        });
+    }
+
+    if unlocked_tokens == 0 {
+        // The holder is at the cap; everything waits for a later call
+        msg!(
+            "User {:?}: {} tokens deferred until the balance drops below the cap",
+            user_state.wallet,
+            user_state.deferred_tokens
         );
+        user_state.last_unlocked_milestone = global_state.current_milestone;
+        return Ok(());
+    }
 
-        solana_program::program::invoke(
-            &create_instruction,
-            &[project_wallet_account.clone()],
-        )?;
+    if released_tokens > 0 {
+        emit!(DeferredReleased {
+            wallet: user_state.wallet,
+            released_tokens,
+            remaining_deferred: user_state.deferred_tokens,
+        });
+    }
 
-        // Initialize the user state
-        let user_state = MilestoneUnlockAccount {
-            wallet: *user_wallet,
-            ata: get_associated_token_address(user_wallet, &global_state.token_mint),
-            total_locked_tokens: locked_amount,
-            unlocked_tokens: 0,
-            last_unlocked_milestone: 0,
-        };
+    // Apply the configured tax and split it between Burn, Marketing and Treasury
+    let TaxSplit { tax, burn: tax_burn, marketing: tax_marketing, treasury: tax_treasury } =
+        calculate_tax(unlocked_tokens, global_state)?;
+
+    let post_tax_tokens = unlocked_tokens
+        .checked_sub(tax)
+        .ok_or(ErrorCode::ArithmeticOverflow)?; // Protect against negative results (underflow)
 
-        user_state.serialize(&mut *user_state_account.unwrap().data.borrow_mut())?;
-        msg!("New user PDA created for wallet: {:?} with locked tokens: {}", user_wallet, locked_amount);
+    // Transfer post-tax tokens to the user
+    vault_transfer(accounts, user_ata, post_tax_tokens, vault_signer_seeds)?;
+
+    // Burn the burn share of the tax, or park it in the Burn Wallet
+    match global_state.burn_mode {
+        BurnMode::TransferToBurnWallet => {
+            vault_transfer(accounts, &accounts.burn_wallet, tax_burn, vault_signer_seeds)?;
+        }
+        BurnMode::Burn => {
+            let cpi_burn = token_interface::Burn {
+                mint: accounts.token_mint.clone(),
+                from: accounts.vault.clone(),
+                authority: accounts.vault_authority.clone(),
+            };
+
+            token_interface::burn(
+                CpiContext::new_with_signer(
+                    accounts.token_program.clone(),
+                    cpi_burn,
+                    vault_signer_seeds,
+                ),
+                tax_burn,
+            )?;
+
+            // Only tokens actually destroyed count as burned
+            global_state.total_burned = global_state
+                .total_burned
+                .checked_add(tax_burn)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
     }
 
+    // Transfer tax to Marketing Wallet
+    vault_transfer(accounts, &accounts.marketing_wallet, tax_marketing, vault_signer_seeds)?;
+
+    // Transfer tax to Treasury Wallet (only when a treasury share is configured)
+    if tax_treasury > 0 {
+        let treasury_wallet = accounts
+            .treasury_wallet
+            .as_ref()
+            .ok_or(ErrorCode::InvalidTreasuryWallet)?;
+        vault_transfer(accounts, treasury_wallet, tax_treasury, vault_signer_seeds)?;
+    }
+
+    // Update user state
+    user_state.total_locked_tokens = user_state
+        .total_locked_tokens
+        .checked_sub(unlocked_tokens)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    user_state.unlocked_tokens = user_state
+        .unlocked_tokens
+        .checked_add(unlocked_tokens)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    user_state.last_unlocked_milestone = global_state.current_milestone;
+    global_state.outstanding_allocations = global_state
+        .outstanding_allocations
+        .checked_sub(unlocked_tokens)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+    // Emit milestone processed event
+    emit!(MilestoneProcessed {
+        wallet: user_state.wallet,
+        unlocked_tokens: post_tax_tokens, // Post-tax tokens unlocked
+        milestone_index: global_state.current_milestone,
+        tax,                              // Total tax amount deducted
+        tax_bps: global_state.tax_bps,    // Tax rate applied
+        burn_tax: tax_burn,               // Tax sent to Burn Wallet
+        marketing_tax: tax_marketing,     // Tax sent to Marketing Wallet
+        treasury_tax: tax_treasury,       // Tax sent to Treasury Wallet
+        total_burned: global_state.total_burned, // Running total of burned tax
+    });
+
+    msg!(
+        "User {:?}: Unlocked {} tokens ({} taxed, {} to Burn Wallet, {} to Marketing Wallet, {} to Treasury Wallet)",
+        user_state.wallet,
+        post_tax_tokens,
+        tax,
+        tax_burn,
+        tax_marketing,
+        tax_treasury
+    );
+
     Ok(())
 }
 
-/// Helper function to find a specific account in account slice
-fn find_account<'a, F>(accounts: &'a [AccountInfo<'a>], predicate: F) -> Result<&'a AccountInfo<'a>>
-where
-    F: Fn(&AccountInfo) -> bool,
-{
-    accounts
-        .iter()
-        .find(|acc| predicate(acc))
-        .ok_or(ErrorCode::AccountNotFound.into())
+/// Tax deducted from an unlock and its split between the tax wallets
+struct TaxSplit {
+    tax: u64,
+    burn: u64,
+    marketing: u64,
+    treasury: u64,
 }
 
-/// Check if an account is a valid token account
-fn is_token_account(account: &AccountInfo) -> bool {
-    account.data_len() == spl_token_2022::state::Account::LEN
+/// Applies the configured tax rate to `amount`; rounding dust goes to marketing
+fn calculate_tax(amount: u64, global_state: &GlobalState) -> Result<TaxSplit> {
+    let bps_of = |value: u64, bps: u16| -> Result<u64> {
+        let result = (value as u128)
+            .checked_mul(bps as u128)
+            .ok_or(ErrorCode::ArithmeticOverflow)?
+            .checked_div(BPS_DENOMINATOR as u128)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        Ok(result as u64)
+    };
+
+    let tax = bps_of(amount, global_state.tax_bps)?;
+    let burn = bps_of(tax, global_state.burn_share_bps)?;
+    let treasury = bps_of(tax, global_state.treasury_share_bps)?;
+    let marketing = tax
+        .checked_sub(burn)
+        .and_then(|rest| rest.checked_sub(treasury))
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+    Ok(TaxSplit { tax, burn, marketing, treasury })
 }
 
-/// Check if an account is a valid mint account
-fn is_mint_account(account: &AccountInfo) -> bool {
-    account.data_len() == spl_token_2022::state::Mint::LEN
+/// Stores a new market cap reading and appends it to the TWAP ring buffer.
+/// Readings closer together than the observation interval only update the spot value.
+fn record_market_cap(global_state: &mut GlobalState, market_cap: u64, now: i64) -> Result<()> {
+    global_state.current_market_cap = market_cap;
+    track_milestone_crossings(global_state, market_cap, now);
+
+    let interval = (global_state.twap_window_seconds / OBSERVATIONS_PER_WINDOW).max(1);
+    let cumulative = if global_state.observation_count == 0 {
+        0
+    } else {
+        let newest = global_state.observations[global_state.observation_head as usize];
+        if now.saturating_sub(newest.timestamp) < interval {
+            return Ok(());
+        }
+        cumulative_at(&newest, now)?
+    };
+
+    let head = if global_state.observation_count == 0 {
+        0
+    } else {
+        (global_state.observation_head as usize + 1) % MAX_OBSERVATIONS
+    };
+    global_state.observations[head] = MarketCapObservation {
+        timestamp: now,
+        market_cap,
+        cumulative,
+    };
+    global_state.observation_head = head as u8;
+    if (global_state.observation_count as usize) < MAX_OBSERVATIONS {
+        global_state.observation_count += 1;
+    }
+
+    emit!(MarketCapObserved {
+        market_cap,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+/// Accumulated `market_cap * seconds` from the first observation up to `timestamp`
+fn cumulative_at(observation: &MarketCapObservation, timestamp: i64) -> Result<u128> {
+    let elapsed = timestamp
+        .checked_sub(observation.timestamp)
+        .filter(|elapsed| *elapsed >= 0)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    (observation.market_cap as u128)
+        .checked_mul(elapsed as u128)
+        .and_then(|area| area.checked_add(observation.cumulative))
+        .ok_or(ErrorCode::ArithmeticOverflow.into())
+}
+
+/// Time-weighted average market cap over the last `twap_window_seconds`
+fn market_cap_twap(global_state: &GlobalState, now: i64) -> Result<u64> {
+    let window = global_state.twap_window_seconds;
+    require!(window > 0, ErrorCode::InvalidTwapWindow);
+    require!(global_state.observation_count > 0, ErrorCode::InsufficientObservations);
+
+    let count = global_state.observation_count as usize;
+    let head = global_state.observation_head as usize;
+    let window_start = now.checked_sub(window).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+    // Walk back from the newest observation to the last one at or before the window start
+    let start = (0..count)
+        .map(|age| global_state.observations[(head + MAX_OBSERVATIONS - age) % MAX_OBSERVATIONS])
+        .find(|observation| observation.timestamp <= window_start)
+        .ok_or(ErrorCode::InsufficientObservations)?;
+
+    let newest = global_state.observations[head];
+    let area = cumulative_at(&newest, now)?
+        .checked_sub(cumulative_at(&start, window_start)?)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+    u64::try_from(area / window as u128).map_err(|_| ErrorCode::ArithmeticOverflow.into())
+}
+
+/// Market cap in whole USD: `price * 10^expo` per whole token times the supply in whole tokens
+pub fn market_cap_from_price(price: u64, expo: i32, supply: u64, decimals: u8) -> Result<u64> {
+    let value = (price as u128)
+        .checked_mul(supply as u128)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+    // Fold the price exponent and the mint decimals into a single power of ten
+    let exponent = expo
+        .checked_sub(decimals as i32)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    let scale = 10u128
+        .checked_pow(exponent.unsigned_abs())
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    let market_cap = if exponent >= 0 {
+        value.checked_mul(scale).ok_or(ErrorCode::ArithmeticOverflow)?
+    } else {
+        value / scale
+    };
+
+    u64::try_from(market_cap).map_err(|_| ErrorCode::InvalidMarketCapValue.into())
+}
+
+/// Creates a program-owned user state PDA, even if someone pre-funded its address
+fn create_user_state_account<'info>(
+    payer: &AccountInfo<'info>,
+    user_state: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    program_id: &Pubkey,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let space = 8 + MilestoneUnlockAccount::LEN;
+    let required_lamports = Rent::get()?.minimum_balance(space);
+    let signer_seeds: &[&[&[u8]]] = &[seeds];
+
+    if user_state.lamports() == 0 {
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: user_state.clone(),
+                },
+                signer_seeds,
+            ),
+            required_lamports,
+            space as u64,
+            program_id,
+        )?;
+    } else {
+        // The address already holds lamports, so top it up and allocate it in place
+        let top_up = required_lamports.saturating_sub(user_state.lamports());
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    system_program::Transfer {
+                        from: payer.clone(),
+                        to: user_state.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Allocate { account_to_allocate: user_state.clone() },
+                signer_seeds,
+            ),
+            space as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Assign { account_to_assign: user_state.clone() },
+                signer_seeds,
+            ),
+            program_id,
+        )?;
+    }
+
+    Ok(())
 }
 
 fn get_token_balance(token_account: &AccountInfo) -> Result<u64> {
-    // Use the spl_token::state::Account to access the token balance
-    let data = spl_token::state::Account::unpack(&token_account.data.borrow()).map_err(|_| ErrorCode::TokenAccountCreationFailed)?;
-    Ok(data.amount)
+    // Token-2022 accounts may carry extensions after the base layout, which SPL Token accounts share
+    let data = token_account.try_borrow_data()?;
+    let account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)
+        .map_err(|_| ErrorCode::TokenAccountCreationFailed)?;
+    Ok(account.base.amount)
 }
 
-pub fn raydium_swap_instruction(
-    liquidity_pool: Pubkey,
-    source: Pubkey,
-    amount: u64,
-) -> Result<Instruction> {
-    let swap_ix = Instruction {
-        program_id: spl_token_2022::id(), // Raydium program address
+/// Swaps `amount_in` vault tokens on the pinned Raydium AMM v4 pool, signed by the vault authority PDA
+fn auto_sell_in_liquidity_pool(accounts: &AutoSell, amount_in: u64, minimum_amount_out: u64) -> Result<()> {
+    let global_state = &accounts.global_state;
+    let global_state_key = global_state.key();
+    let vault_authority_seeds: &[&[u8]] = &[
+        VAULT_AUTHORITY_SEED,
+        global_state_key.as_ref(),
+        &[global_state.vault_authority_bump],
+    ];
+
+    solana_program::program::invoke_signed(
+        &raydium_swap_instruction(accounts, amount_in, minimum_amount_out),
+        &[
+            accounts.token_program.to_account_info(),
+            accounts.amm.to_account_info(),
+            accounts.amm_authority.to_account_info(),
+            accounts.amm_open_orders.to_account_info(),
+            accounts.amm_target_orders.to_account_info(),
+            accounts.pool_coin_token_account.to_account_info(),
+            accounts.pool_pc_token_account.to_account_info(),
+            accounts.serum_program.to_account_info(),
+            accounts.serum_market.to_account_info(),
+            accounts.serum_bids.to_account_info(),
+            accounts.serum_asks.to_account_info(),
+            accounts.serum_event_queue.to_account_info(),
+            accounts.serum_coin_vault.to_account_info(),
+            accounts.serum_pc_vault.to_account_info(),
+            accounts.serum_vault_signer.to_account_info(),
+            accounts.vault.to_account_info(),
+            accounts.quote_destination.to_account_info(),
+            accounts.vault_authority.to_account_info(),
+            accounts.raydium_program.to_account_info(),
+        ],
+        &[vault_authority_seeds],
+    )?;
+
+    Ok(())
+}
+
+/// Raydium AMM v4 `swap_base_in`, selling vault tokens for the pool's quote token
+fn raydium_swap_instruction(accounts: &AutoSell, amount_in: u64, minimum_amount_out: u64) -> Instruction {
+    let mut data = Vec::with_capacity(17);
+    data.push(RAYDIUM_SWAP_BASE_IN_TAG);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+
+    Instruction {
+        program_id: accounts.raydium_program.key(),
         accounts: vec![
-            AccountMeta::new_readonly(liquidity_pool, false), // Raydium liquidity pool
-            AccountMeta::new(source, false), // Source account (Project Wallet)
+            AccountMeta::new_readonly(accounts.token_program.key(), false),
+            AccountMeta::new(accounts.amm.key(), false),
+            AccountMeta::new_readonly(accounts.amm_authority.key(), false),
+            AccountMeta::new(accounts.amm_open_orders.key(), false),
+            AccountMeta::new(accounts.amm_target_orders.key(), false),
+            AccountMeta::new(accounts.pool_coin_token_account.key(), false),
+            AccountMeta::new(accounts.pool_pc_token_account.key(), false),
+            AccountMeta::new_readonly(accounts.serum_program.key(), false),
+            AccountMeta::new(accounts.serum_market.key(), false),
+            AccountMeta::new(accounts.serum_bids.key(), false),
+            AccountMeta::new(accounts.serum_asks.key(), false),
+            AccountMeta::new(accounts.serum_event_queue.key(), false),
+            AccountMeta::new(accounts.serum_coin_vault.key(), false),
+            AccountMeta::new(accounts.serum_pc_vault.key(), false),
+            AccountMeta::new_readonly(accounts.serum_vault_signer.key(), false),
+            AccountMeta::new(accounts.vault.key(), false), // Source: the vault
+            AccountMeta::new(accounts.quote_destination.key(), false), // Proceeds
+            AccountMeta::new_readonly(accounts.vault_authority.key(), true), // Vault owner
         ],
-        data: amount.to_le_bytes().to_vec(), // Custom instruction data (Raydium swap specifics, e.g., amounts, slippage)
-    };
-    Ok(swap_ix)
-}
\ No newline at end of file
+        data,
+    }
+}
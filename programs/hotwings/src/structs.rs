@@ -1,6 +1,9 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{ Mint, Token, TokenAccount };
-use anchor_spl::token_interface::Token2022;
+use anchor_lang::prelude::borsh;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{ Mint, Token2022, TokenAccount, TokenInterface };
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
 use crate::consts::*;
 use crate::errors::ErrorCode;
 
@@ -18,6 +21,7 @@ pub struct GlobalState {
     /// CHECK: This is a standard wallet account, and the program will verify its usage.                // Marketing wallet account (32 bytes)
     pub project_wallet: Pubkey,                   // Project wallet account (32 bytes)
     pub milestones: [Milestone; MAX_MILESTONES],  // Milestone data (variable size)
+    pub milestone_count: u8,                      // Number of configured milestones (1 byte)
     pub current_market_cap: u64,                  // Current market cap (8 bytes)
     pub current_milestone: u8,                    // Current milestone index (1 byte)
     pub user_count: u64,                          // Total user count (8 bytes)
@@ -26,6 +30,33 @@ pub struct GlobalState {
     pub unlock_complete: bool,                    // Full unlock flag (1 byte) 
     pub raydium_program_id: Pubkey,               // Program ID of Raydium AMM (32 bytes)
     pub liquidity_pool: Pubkey,                   // Raydium liquidity pool account (32 bytes)
+    pub tax_bps: u16,                             // Unlock tax in basis points (2 bytes)
+    pub burn_share_bps: u16,                      // Burn share of the tax in basis points (2 bytes)
+    pub marketing_share_bps: u16,                 // Marketing share of the tax in basis points (2 bytes)
+    pub treasury_share_bps: u16,                  // Treasury share of the tax in basis points (2 bytes)
+    pub treasury_wallet: Pubkey,                  // Treasury wallet, only used when its share is set (32 bytes)
+    pub burn_mode: BurnMode,                      // Burn on-chain or transfer to the burn wallet (1 byte)
+    pub total_burned: u64,                        // Running total of tax burned in Burn mode (8 bytes)
+    pub vault: Pubkey,                            // Token vault holding the locked supply (32 bytes)
+    pub vault_authority_bump: u8,                 // Bump of the PDA that owns the vault (1 byte)
+    pub crank_reward: u64,                        // Tokens paid to whoever runs process_milestones (8 bytes)
+    pub pyth_price_feed: Pubkey,                  // Pyth USD price account for the token (32 bytes)
+    pub max_price_staleness: i64,                 // Max age of a Pyth price in seconds (8 bytes)
+    pub max_price_confidence_bps: u16,            // Max confidence interval relative to price (2 bytes)
+    pub observations: [MarketCapObservation; MAX_OBSERVATIONS], // TWAP ring buffer (fixed size)
+    pub observation_head: u8,                     // Index of the newest observation (1 byte)
+    pub observation_count: u8,                    // Number of filled observations (1 byte)
+    pub twap_window_seconds: i64,                 // TWAP window for milestones, 0 = spot (8 bytes)
+    pub milestone_crossed_at: [i64; MAX_MILESTONES], // When each pending threshold was first crossed, 0 = below
+    pub hold_cap_mode: HoldCapMode,               // How the anti-whale hold cap is expressed (1 byte)
+    pub hold_cap: u64,                            // Anti-whale hold cap, in the units of `hold_cap_mode` (8 bytes)
+    pub token_decimals: u8,                       // Mint decimals, read at initialization (1 byte)
+    pub pending_authority: Pubkey,                // Proposed authority awaiting acceptance, default = none (32 bytes)
+    pub bump: u8,                                 // Bump of the mint-seeded GlobalState PDA (1 byte)
+    pub multisig: Pubkey,                         // Multisig that must approve sensitive changes, default = none (32 bytes)
+    pub crank_reward_pool: u64,                   // Vault tokens set aside for crank rewards, never user allocations (8 bytes)
+    pub outstanding_allocations: u64,             // Registered allocations not yet paid out of the vault (8 bytes)
+    pub auto_sold: bool,                          // Auto-sell already ran, it is one-shot (1 byte)
 }
 
 
@@ -40,6 +71,7 @@ impl GlobalState {
         + 32                                            // marketing_wallet
         + 32                                            // project_wallet
         + (MILESTONE_SIZE * MAX_MILESTONES)       // Fixed-size milestones
+        + 1                                             // milestone_count
         + 8                                             // current_market_cap
         + 1                                             // current_milestone
         + 8                                             // user_count
@@ -47,15 +79,135 @@ impl GlobalState {
         + 4 + (32 * MAX_EXEMPTED_WALLETS)         // Exempted_wallets (Vec metadata + max size)
         + 1                                            // unlock_complete flag
         + 32
-        + 32; 
+        + 32
+        + 2                                             // tax_bps
+        + 2                                             // burn_share_bps
+        + 2                                             // marketing_share_bps
+        + 2                                             // treasury_share_bps
+        + 32                                            // treasury_wallet
+        + 1                                             // burn_mode
+        + 8                                             // total_burned
+        + 32                                            // vault
+        + 1                                             // vault_authority_bump
+        + 8                                             // crank_reward
+        + 32                                            // pyth_price_feed
+        + 8                                             // max_price_staleness
+        + 2                                             // max_price_confidence_bps
+        + (OBSERVATION_SIZE * MAX_OBSERVATIONS)   // Fixed-size TWAP observations
+        + 1                                             // observation_head
+        + 1                                             // observation_count
+        + 8                                             // twap_window_seconds
+        + (8 * MAX_MILESTONES)                    // milestone_crossed_at
+        + 1                                             // hold_cap_mode
+        + 8                                             // hold_cap
+        + 1                                             // token_decimals
+        + 32                                            // pending_authority
+        + 1                                             // bump
+        + 32                                            // multisig
+        + 8                                             // crank_reward_pool
+        + 8                                             // outstanding_allocations
+        + 1;                                            // auto_sold
+}
+
+// Keys allowed to run each class of privileged instruction, one account per GlobalState.
+// `GlobalState.authority` only grants and revokes roles and hands over ownership.
+#[account]
+#[derive(Default)]
+pub struct Roles {
+    pub global_state: Pubkey,       // Campaign these roles belong to (32 bytes)
+    pub config_admin: Pubkey,       // Milestones, tax, hold cap, pool and oracle config, user registration (32 bytes)
+    pub oracle_updater: Pubkey,     // Manual and pool market cap updates (32 bytes)
+    pub exemptions_manager: Pubkey, // Adds and removes exempted wallets (32 bytes)
+    pub treasury_signer: Pubkey,    // Signs treasury auto-sells (32 bytes)
+    pub bump: u8,                   // Bump of the Roles PDA (1 byte)
+}
+
+impl Roles {
+    pub const LEN: usize = 32 + 32 + 32 + 32 + 32 + 1;
+
+    /// Current holder of `role`, `Pubkey::default()` when revoked
+    pub fn holder(&self, role: Role) -> Pubkey {
+        match role {
+            Role::ConfigAdmin => self.config_admin,
+            Role::OracleUpdater => self.oracle_updater,
+            Role::ExemptionsManager => self.exemptions_manager,
+            Role::TreasurySigner => self.treasury_signer,
+        }
+    }
+
+    pub fn set_holder(&mut self, role: Role, holder: Pubkey) {
+        match role {
+            Role::ConfigAdmin => self.config_admin = holder,
+            Role::OracleUpdater => self.oracle_updater = holder,
+            Role::ExemptionsManager => self.exemptions_manager = holder,
+            Role::TreasurySigner => self.treasury_signer = holder,
+        }
+    }
+}
+
+// M-of-N signer set guarding sensitive admin actions, one account per GlobalState
+#[account]
+pub struct Multisig {
+    pub global_state: Pubkey, // Campaign this multisig guards (32 bytes)
+    pub signers: Vec<Pubkey>, // Keys allowed to propose and approve (up to MAX_MULTISIG_SIGNERS)
+    pub threshold: u8,        // Approvals needed before an action executes (1 byte)
+    pub proposal_count: u64,  // Index of the next proposal (8 bytes)
+    pub bump: u8,             // Bump of the Multisig PDA (1 byte)
+}
+
+impl Multisig {
+    pub const LEN: usize = 32
+        + 4 + (32 * MAX_MULTISIG_SIGNERS)   // signers (Vec metadata + max size)
+        + 1                                 // threshold
+        + 8                                 // proposal_count
+        + 1;                                // bump
+}
+
+// A queued admin action and the signers who approved it
+#[account]
+pub struct AdminProposal {
+    pub multisig: Pubkey,       // Multisig the proposal belongs to (32 bytes)
+    pub index: u64,             // Position in the multisig's proposal sequence (8 bytes)
+    pub proposer: Pubkey,       // Signer who created the proposal (32 bytes)
+    pub action: AdminAction,    // Change applied once the threshold is met
+    pub approvals: Vec<Pubkey>, // Signers who approved, the proposer included
+    pub executed: bool,         // Set once the action has been applied (1 byte)
+    pub bump: u8,               // Bump of the proposal PDA (1 byte)
+}
+
+impl AdminProposal {
+    pub const LEN: usize = 32
+        + 8                                 // index
+        + 32                                // proposer
+        + AdminAction::MAX_LEN              // action
+        + 4 + (32 * MAX_MULTISIG_SIGNERS)   // approvals (Vec metadata + max size)
+        + 1                                 // executed
+        + 1;                                // bump
 }
 
 #[derive(Accounts)]
 pub struct InitializeProgram<'info> {
-    #[account(init, payer = authority, space = 8 + GlobalState::LEN)]
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + GlobalState::LEN,
+        seeds = [GLOBAL_STATE_SEED, token_mint.key().as_ref()], // One config per mint
+        bump
+    )]
     pub global_state: Account<'info, GlobalState>,
-    /// The Token Mint account
-    pub token_mint: Account<'info, Mint>, // Correct type for SPL Token Mint
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Roles::LEN,
+        seeds = [ROLES_SEED, global_state.key().as_ref()],
+        bump
+    )]
+    pub roles: Account<'info, Roles>, // Every role starts with the initializing authority
+    /// The Token Mint account; only its mint authority may claim the config PDA for it
+    #[account(
+        constraint = token_mint.mint_authority == COption::Some(authority.key()) @ ErrorCode::Unauthorized
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>, // SPL Token or Token-2022 mint
     /// The Burn Wallet
     /// CHECK: This must be a standard wallet. Use constraints to validate.
     #[account(
@@ -78,11 +230,27 @@ pub struct InitializeProgram<'info> {
         constraint = *project_wallet.owner == solana_program::system_program::ID @ ErrorCode::InvalidProjectWallet // Ensure it's not owned by another program
     )]
     pub project_wallet: AccountInfo<'info>, // Project wallet
+    /// Token vault holding the locked supply, owned by the vault authority PDA
+    #[account(
+        init,
+        payer = authority,
+        seeds = [VAULT_SEED, global_state.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: PDA that signs transfers out of the vault; holds no data
+    #[account(seeds = [VAULT_AUTHORITY_SEED, global_state.key().as_ref()], bump)]
+    pub vault_authority: AccountInfo<'info>,
     /// Admin authority
     #[account(mut)]
     pub authority: Signer<'info>, // Admin authority
     /// System program
     pub system_program: Program<'info, System>, // System Program
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token or Token-2022 program
+    pub rent: Sysvar<'info, Rent>, // Rent sysvar
 }
 
 // User entry structure (used for registering users)
@@ -96,8 +264,10 @@ pub struct UserEntry {
 pub struct MilestoneUnlockAccount {
     pub wallet: Pubkey,                // User's main wallet address
     pub ata: Pubkey,                   // User's associated token account (ATA)
-    pub total_locked_tokens: u64,      // Total locked tokens
+    pub allocated_tokens: u64,         // Original allocation, the base for milestone percentages
+    pub total_locked_tokens: u64,      // Tokens still locked
     pub unlocked_tokens: u64,          // Tokens unlocked so far
+    pub deferred_tokens: u64,          // Unlocked tokens held back by the anti-whale cap
     pub last_unlocked_milestone: u8,   // Last milestone processed
 }
 
@@ -105,63 +275,439 @@ impl MilestoneUnlockAccount {
     pub const LEN: usize = 8 // Discriminator
         + 32 // wallet
         + 32 // ata
+        + 8  // allocated_tokens
         + 8  // total_locked_tokens
         + 8  // unlocked_tokens
+        + 8  // deferred_tokens
         + 1; // last_unlocked_milestone
 }
 
+// One market cap reading in the TWAP ring buffer
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
+pub struct MarketCapObservation {
+    pub timestamp: i64,   // When the reading was recorded
+    pub market_cap: u64,  // Market cap from this point until the next observation
+    pub cumulative: u128, // Sum of market_cap * seconds up to `timestamp`
+}
+
+// How the burn share of the unlock tax leaves circulation
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BurnMode {
+    #[default]
+    TransferToBurnWallet, // Send it to the burn wallet (supply unchanged)
+    Burn,                 // Burn it straight from the vault (supply shrinks)
+}
+
+// Privileged roles stored in the Roles account
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    ConfigAdmin,
+    OracleUpdater,
+    ExemptionsManager,
+    TreasurySigner,
+}
+
+// Sensitive admin changes that need multisig approval once a multisig is configured
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub enum AdminAction {
+    ProposeAuthority { new_authority: Pubkey }, // Nominated key still has to call accept_authority
+    CancelAuthorityTransfer,
+    UpdateLiquidityPool { liquidity_pool: Pubkey },
+    UpdateRaydiumProgramId { raydium_program_id: Pubkey },
+    UpdateTaxConfig {
+        tax_bps: u16,
+        burn_share_bps: u16,
+        marketing_share_bps: u16,
+        treasury_share_bps: u16,
+        treasury_wallet: Pubkey,
+    },
+    UpdateMilestones { milestones: Vec<Milestone> },
+    UpdateBurnMode { burn_mode: BurnMode },
+    UpdatePythConfig {
+        price_feed: Pubkey,
+        max_price_staleness: i64,
+        max_price_confidence_bps: u16,
+    },
+    UpdateTwapWindow { twap_window_seconds: i64 },
+    UpdateMultisig { signers: Vec<Pubkey>, threshold: u8 },
+}
+
+impl AdminAction {
+    const MILESTONES_LEN: usize = 4 + (MILESTONE_SIZE * MAX_MILESTONES);
+    const MULTISIG_LEN: usize = 4 + (32 * MAX_MULTISIG_SIGNERS) + 1;
+
+    // Variant tag plus the largest variant
+    pub const MAX_LEN: usize = 1 + if Self::MILESTONES_LEN > Self::MULTISIG_LEN {
+        Self::MILESTONES_LEN
+    } else {
+        Self::MULTISIG_LEN
+    };
+}
+
+// How the anti-whale hold cap is expressed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HoldCapMode {
+    Absolute,  // Raw token amount, in base units
+    #[default]
+    SupplyBps, // Basis points of the mint's current supply
+    UiAmount,  // Whole tokens, scaled by the mint's decimals
+}
+
 // Milestone definition
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
 pub struct Milestone {
     pub market_cap: u64, // Threshold market cap
-    pub unlock_percent: u8, // Percentage of tokens unlocked
+    pub unlock_percent: u8, // Cumulative percentage of the allocation unlocked
+    pub sustain_seconds: u32, // Time the market cap must stay above the threshold, 0 = unlock immediately
+    pub hold_cap: u64, // Hold cap once this milestone is reached (same units as GlobalState), 0 = unchanged
 }
 
 #[derive(Accounts)]
 pub struct RegisterUsers<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
     pub global_state: Account<'info, GlobalState>, // Global state account
+    #[account(
+        seeds = [ROLES_SEED, global_state.key().as_ref()],
+        bump = roles.bump,
+        constraint = roles.config_admin == authority.key() @ ErrorCode::Unauthorized // Requires the config admin role
+    )]
+    pub roles: Account<'info, Roles>, // Role assignments for this campaign
     #[account(mut)]
     ///CHECK: This is a standard wallet account, and the program will verify its usage
     pub authority: Signer<'info>, // Authority (payer for creating the user state PDA)
+    /// Token mint, read for the supply when the hold cap is a share of it
+    #[account(address = global_state.token_mint @ ErrorCode::AccountNotFound)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
     pub rent: Sysvar<'info, Rent>, // Rent system variable
     pub system_program: Program<'info, System>, // System program account
-    pub token_program: Program<'info, Token>, // SPL Token program
-    /// CHECK: Associated Token Program (unchecked)
-    pub associated_token_program: AccountInfo<'info>,
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token or Token-2022 program
+    pub associated_token_program: Program<'info, AssociatedToken>, // Creates the user ATAs
+    // Remaining accounts: one user state PDA per entry (seeds = [USER_STATE_SEED, global_state, entry.wallet]),
+    // in the same order as the entries, followed by a (wallet, ATA) pair per entry, in the same order.
+}
+
+#[derive(Accounts)]
+pub struct UnlockTokens<'info> {
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    /// Vault holding the locked supply
+    #[account(mut, address = global_state.vault @ ErrorCode::InvalidVault)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: PDA that owns the vault; only used as a CPI signer
     #[account(
-        init, // Initialize the account (user PDA)
-        seeds = [b"user_state", authority.key().as_ref()], // Derive PDA using static seed + user key
-        bump, // Auto-derive PDA bump
-        payer = authority, // Specify who pays for account creation
-        space = 8 + MilestoneUnlockAccount::LEN // Allocate space for MilestoneUnlockAccount state
+        seeds = [VAULT_AUTHORITY_SEED, global_state.key().as_ref()],
+        bump = global_state.vault_authority_bump
+    )]
+    pub vault_authority: AccountInfo<'info>,
+    /// Keeper running the crank; anyone may call it
+    pub caller: Signer<'info>,
+    /// Token account receiving the crank reward
+    #[account(
+        mut,
+        constraint = caller_token_account.mint == global_state.token_mint @ ErrorCode::UserWalletNotFound
     )]
-     ///CHECK: User state PDA account
-    pub user_state: Account<'info, MilestoneUnlockAccount>, // User PDA for milestone token tracking
+    pub caller_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// Token mint, needed for checked transfers
+    #[account(address = global_state.token_mint @ ErrorCode::AccountNotFound)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
-pub struct UnlockTokens<'info> {
-    #[account(mut)]
+pub struct AutoSell<'info> {
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
     pub global_state: Account<'info, GlobalState>,
+    #[account(
+        seeds = [ROLES_SEED, global_state.key().as_ref()],
+        bump = roles.bump,
+        constraint = roles.treasury_signer == authority.key() @ ErrorCode::Unauthorized // Requires the treasury signer role
+    )]
+    pub roles: Account<'info, Roles>, // Role assignments for this campaign
+    pub authority: Signer<'info>, // Treasury signer selling from the vault
+    /// Vault holding the locked supply, the swap source
+    #[account(mut, address = global_state.vault @ ErrorCode::InvalidVault)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: PDA that owns the vault; signs the swap as its owner
+    #[account(
+        seeds = [VAULT_AUTHORITY_SEED, global_state.key().as_ref()],
+        bump = global_state.vault_authority_bump
+    )]
+    pub vault_authority: AccountInfo<'info>,
+    /// Quote token account receiving the proceeds
+    #[account(
+        mut,
+        constraint = quote_destination.owner == global_state.project_wallet @ ErrorCode::InvalidProjectWallet
+    )]
+    pub quote_destination: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: Raydium AMM v4 program pinned by the global state
+    #[account(address = global_state.raydium_program_id @ ErrorCode::InvalidLiquidityPool)]
+    pub raydium_program: AccountInfo<'info>,
+    /// CHECK: Raydium AMM pool pinned by the global state
+    #[account(
+        mut,
+        address = global_state.liquidity_pool @ ErrorCode::InvalidLiquidityPool,
+        owner = global_state.raydium_program_id @ ErrorCode::InvalidLiquidityPool
+    )]
+    pub amm: AccountInfo<'info>,
+    /// CHECK: Raydium AMM authority; validated by Raydium
+    pub amm_authority: AccountInfo<'info>,
+    /// CHECK: Raydium AMM open orders; validated by Raydium
+    #[account(mut)]
+    pub amm_open_orders: AccountInfo<'info>,
+    /// CHECK: Raydium AMM target orders; validated by Raydium
     #[account(mut)]
-    pub project_wallet: Account<'info, TokenAccount>,
-    pub project_wallet_authority: Signer<'info>,
+    pub amm_target_orders: AccountInfo<'info>,
+    /// CHECK: Pool vault of this token; validated by Raydium
     #[account(mut)]
-    pub burn_wallet: Account<'info, TokenAccount>,
+    pub pool_coin_token_account: AccountInfo<'info>,
+    /// CHECK: Pool vault of the quote token; validated by Raydium
     #[account(mut)]
-    pub marketing_wallet: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
+    pub pool_pc_token_account: AccountInfo<'info>,
+    /// CHECK: OpenBook program the pool trades on; validated by Raydium
+    pub serum_program: AccountInfo<'info>,
+    /// CHECK: OpenBook market; validated by Raydium
+    #[account(mut)]
+    pub serum_market: AccountInfo<'info>,
+    /// CHECK: OpenBook bids; validated by Raydium
+    #[account(mut)]
+    pub serum_bids: AccountInfo<'info>,
+    /// CHECK: OpenBook asks; validated by Raydium
+    #[account(mut)]
+    pub serum_asks: AccountInfo<'info>,
+    /// CHECK: OpenBook event queue; validated by Raydium
+    #[account(mut)]
+    pub serum_event_queue: AccountInfo<'info>,
+    /// CHECK: OpenBook coin vault; validated by Raydium
+    #[account(mut)]
+    pub serum_coin_vault: AccountInfo<'info>,
+    /// CHECK: OpenBook quote vault; validated by Raydium
+    #[account(mut)]
+    pub serum_pc_vault: AccountInfo<'info>,
+    /// CHECK: OpenBook vault signer; validated by Raydium
+    pub serum_vault_signer: AccountInfo<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct Claim<'info> {
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    /// The registered user pulling their own unlock
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [USER_STATE_SEED, global_state.key().as_ref(), user.key().as_ref()],
+        bump,
+        constraint = user_state.wallet == user.key() @ ErrorCode::Unauthorized
+    )]
+    pub user_state: Account<'info, MilestoneUnlockAccount>,
+    /// The ATA recorded at registration
+    #[account(mut, address = user_state.ata @ ErrorCode::UserWalletNotFound)]
+    pub user_ata: InterfaceAccount<'info, TokenAccount>,
+    /// Vault holding the locked supply
+    #[account(mut, address = global_state.vault @ ErrorCode::InvalidVault)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: PDA that owns the vault; only used as a CPI signer
+    #[account(
+        seeds = [VAULT_AUTHORITY_SEED, global_state.key().as_ref()],
+        bump = global_state.vault_authority_bump
+    )]
+    pub vault_authority: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = burn_wallet.owner == global_state.burn_wallet @ ErrorCode::InvalidBurnWallet
+    )]
+    pub burn_wallet: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = marketing_wallet.owner == global_state.marketing_wallet @ ErrorCode::InvalidMarketingWallet
+    )]
+    pub marketing_wallet: InterfaceAccount<'info, TokenAccount>,
+    /// Token mint, for checked transfers and written to when the burn share is burned on-chain
+    #[account(mut, address = global_state.token_mint @ ErrorCode::AccountNotFound)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    /// Only required when the treasury share of the tax is non-zero
+    #[account(
+        mut,
+        constraint = treasury_wallet.owner == global_state.treasury_wallet @ ErrorCode::InvalidTreasuryWallet
+    )]
+    pub treasury_wallet: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct PushUnlocks<'info> {
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        seeds = [ROLES_SEED, global_state.key().as_ref()],
+        bump = roles.bump,
+        constraint = roles.config_admin == authority.key() @ ErrorCode::Unauthorized // Requires the config admin role
+    )]
+    pub roles: Account<'info, Roles>, // Role assignments for this campaign
+    ///CHECK: This is a standard wallet account, and the program will verify its usage
+    pub authority: Signer<'info>, // Admin authority
+    /// Vault holding the locked supply
+    #[account(mut, address = global_state.vault @ ErrorCode::InvalidVault)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: PDA that owns the vault; only used as a CPI signer
+    #[account(
+        seeds = [VAULT_AUTHORITY_SEED, global_state.key().as_ref()],
+        bump = global_state.vault_authority_bump
+    )]
+    pub vault_authority: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = burn_wallet.owner == global_state.burn_wallet @ ErrorCode::InvalidBurnWallet
+    )]
+    pub burn_wallet: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = marketing_wallet.owner == global_state.marketing_wallet @ ErrorCode::InvalidMarketingWallet
+    )]
+    pub marketing_wallet: InterfaceAccount<'info, TokenAccount>,
+    /// Token mint, for checked transfers and written to when the burn share is burned on-chain
+    #[account(mut, address = global_state.token_mint @ ErrorCode::AccountNotFound)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    /// Only required when the treasury share of the tax is non-zero
+    #[account(
+        mut,
+        constraint = treasury_wallet.owner == global_state.treasury_wallet @ ErrorCode::InvalidTreasuryWallet
+    )]
+    pub treasury_wallet: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMilestones<'info> {
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>, // Global state account
+    #[account(
+        seeds = [ROLES_SEED, global_state.key().as_ref()],
+        bump = roles.bump,
+        constraint = roles.config_admin == authority.key() @ ErrorCode::Unauthorized // Requires the config admin role
+    )]
+    pub roles: Account<'info, Roles>, // Role assignments for this campaign
+    ///CHECK: This is a standard wallet account, and the program will verify its usage
+    pub authority: Signer<'info>, // Admin authority
+}
+
+#[derive(Accounts)]
+pub struct UpdateTaxConfig<'info> {
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>, // Global state account
+    #[account(
+        seeds = [ROLES_SEED, global_state.key().as_ref()],
+        bump = roles.bump,
+        constraint = roles.config_admin == authority.key() @ ErrorCode::Unauthorized // Requires the config admin role
+    )]
+    pub roles: Account<'info, Roles>, // Role assignments for this campaign
+    ///CHECK: This is a standard wallet account, and the program will verify its usage
+    pub authority: Signer<'info>, // Admin authority
+}
+
+#[derive(Accounts)]
+pub struct UpdateCrankReward<'info> {
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>, // Global state account
+    #[account(
+        seeds = [ROLES_SEED, global_state.key().as_ref()],
+        bump = roles.bump,
+        constraint = roles.config_admin == authority.key() @ ErrorCode::Unauthorized // Requires the config admin role
+    )]
+    pub roles: Account<'info, Roles>, // Role assignments for this campaign
+    ///CHECK: This is a standard wallet account, and the program will verify its usage
+    pub authority: Signer<'info>, // Admin authority
+}
+
+#[derive(Accounts)]
+pub struct UpdateHoldCap<'info> {
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>, // Global state account
+    #[account(
+        seeds = [ROLES_SEED, global_state.key().as_ref()],
+        bump = roles.bump,
+        constraint = roles.config_admin == authority.key() @ ErrorCode::Unauthorized // Requires the config admin role
+    )]
+    pub roles: Account<'info, Roles>, // Role assignments for this campaign
+    ///CHECK: This is a standard wallet account, and the program will verify its usage
+    pub authority: Signer<'info>, // Admin authority
+}
+
+#[derive(Accounts)]
+pub struct DepositToVault<'info> {
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>, // Global state account
+    /// Vault holding the locked supply
+    #[account(mut, address = global_state.vault @ ErrorCode::InvalidVault)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    /// Token account the deposit is taken from
+    #[account(
+        mut,
+        constraint = depositor_token_account.mint == global_state.token_mint @ ErrorCode::InvalidVault
+    )]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub depositor: Signer<'info>, // Owner of the depositor token account
+    /// Token mint, needed for checked transfers
+    #[account(address = global_state.token_mint @ ErrorCode::AccountNotFound)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)] 
 pub struct ManageExemptWallet<'info> {
     #[account(
         mut,
-        has_one = authority, // Verify that the provided `Signer` matches the stored `authority`
-        constraint = authority.key() == global_state.authority @ ErrorCode::Unauthorized
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
     )]
     pub global_state: Account<'info, GlobalState>, // Global configuration
+    #[account(
+        seeds = [ROLES_SEED, global_state.key().as_ref()],
+        bump = roles.bump,
+        constraint = roles.exemptions_manager == authority.key() @ ErrorCode::Unauthorized // Requires the exemptions manager role
+    )]
+    pub roles: Account<'info, Roles>, // Role assignments for this campaign
     ///CHECK: This is a standard wallet account, and the program will verify its usage
     pub authority: Signer<'info>, // Admin authority to sign the transaction
 }
@@ -170,37 +716,137 @@ pub struct ManageExemptWallet<'info> {
 pub struct UpdateMarketCap<'info> {
     #[account(
         mut,
-        has_one = authority, // Verify that the provided `Signer` matches the stored `authority`
-        constraint = authority.key() == global_state.authority @ ErrorCode::Unauthorized
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
     )]
     pub global_state: Account<'info, GlobalState>, // Global state account
+    #[account(
+        seeds = [ROLES_SEED, global_state.key().as_ref()],
+        bump = roles.bump,
+        constraint = roles.oracle_updater == authority.key() @ ErrorCode::Unauthorized // Requires the oracle updater role
+    )]
+    pub roles: Account<'info, Roles>, // Role assignments for this campaign
     ///CHECK: This is a standard wallet account, and the program will verify its usage
     pub authority: Signer<'info>,                 // Signer (admin authority)
 }
 
 #[derive(Accounts)]
-pub struct RegisterUserOnTransfer<'info> {
-    #[account(mut)]
-    pub global_state: Account<'info, GlobalState>, // Global state account that stores global data
-    #[account(mut)]
-    pub project_wallet: Account<'info, TokenAccount>, // Project wallet receiving locked tokens
-    #[account(mut)]
+pub struct UpdatePythConfig<'info> {
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>, // Global state account
+    #[account(
+        seeds = [ROLES_SEED, global_state.key().as_ref()],
+        bump = roles.bump,
+        constraint = roles.config_admin == authority.key() @ ErrorCode::Unauthorized // Requires the config admin role
+    )]
+    pub roles: Account<'info, Roles>, // Role assignments for this campaign
     ///CHECK: This is a standard wallet account, and the program will verify its usage
-    pub authority: Signer<'info>, // The signer who calls this transaction (payer for accounts)
-    pub rent: Sysvar<'info, Rent>, // Rent sysvar
-    pub system_program: Program<'info, System>,  // System program (for creating PDAs)
-    pub token_program: Program<'info, Token>, // SPL Token Program for token transfers
-    /// CHECK: Associated Token Program
-    pub associated_token_program: AccountInfo<'info>,
-    #[account(mut)] // User state PDA manually validated inside the function
-    ///CHECK: User state PDA account
-    pub user_state: AccountInfo<'info>, // User-specific PDA account
+    pub authority: Signer<'info>, // Admin authority
+}
+
+#[derive(Accounts)]
+pub struct UpdateMarketCapFromPyth<'info> {
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>, // Global state account
+    /// CHECK: Pyth price account; its address is pinned by the global state and its data is parsed manually
+    #[account(address = global_state.pyth_price_feed @ ErrorCode::InvalidPriceFeed)]
+    pub price_feed: AccountInfo<'info>,
+    /// Token mint, used for the circulating supply
+    #[account(address = global_state.token_mint @ ErrorCode::AccountNotFound)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    pub caller: Signer<'info>, // Anyone may push a fresh oracle reading
+}
+
+#[derive(Accounts)]
+pub struct UpdateMarketCapFromPool<'info> {
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>, // Global state account
+    #[account(
+        seeds = [ROLES_SEED, global_state.key().as_ref()],
+        bump = roles.bump,
+        constraint = roles.oracle_updater == caller.key() @ ErrorCode::Unauthorized // Spot reserves can be pushed around within a block
+    )]
+    pub roles: Account<'info, Roles>, // Role assignments for this campaign
+    /// CHECK: Raydium AMM pool; pinned by the global state, owner checked, data parsed manually
+    #[account(
+        address = global_state.liquidity_pool @ ErrorCode::InvalidLiquidityPool,
+        owner = global_state.raydium_program_id @ ErrorCode::InvalidLiquidityPool
+    )]
+    pub liquidity_pool: AccountInfo<'info>,
+    /// Pool vault holding this token's reserve
+    pub base_vault: InterfaceAccount<'info, TokenAccount>,
+    /// Pool vault holding the quote (USD) reserve
+    pub quote_vault: InterfaceAccount<'info, TokenAccount>,
+    /// Token mint, used for the circulating supply
+    #[account(address = global_state.token_mint @ ErrorCode::AccountNotFound)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    pub caller: Signer<'info>, // Oracle updater pushing a fresh pool reading
+}
+
+#[derive(Accounts)]
+pub struct RecordMarketCapObservation<'info> {
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>, // Global state account
+}
+
+#[derive(Accounts)]
+pub struct ReadMarketCap<'info> {
+    #[account(
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>, // Global state account
+}
+
+#[derive(Accounts)]
+pub struct UpdateTwapWindow<'info> {
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>, // Global state account
+    #[account(
+        seeds = [ROLES_SEED, global_state.key().as_ref()],
+        bump = roles.bump,
+        constraint = roles.config_admin == authority.key() @ ErrorCode::Unauthorized // Requires the config admin role
+    )]
+    pub roles: Account<'info, Roles>, // Role assignments for this campaign
+    ///CHECK: This is a standard wallet account, and the program will verify its usage
+    pub authority: Signer<'info>, // Admin authority
 }
 
 #[derive(Accounts)]
 pub struct RegisterHook<'info> {
+    #[account(
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>, // Global state account
+    #[account(
+        seeds = [ROLES_SEED, global_state.key().as_ref()],
+        bump = roles.bump,
+        constraint = roles.config_admin == authority.key() @ ErrorCode::Unauthorized // Requires the config admin role
+    )]
+    pub roles: Account<'info, Roles>, // Role assignments for this campaign
     /// The Token-2022 mint account (target for the transfer hook update).
-    #[account(mut)]
+    #[account(mut, address = global_state.token_mint @ ErrorCode::AccountNotFound)]
     ///CHECK: Token mint account
     pub token_mint: AccountInfo<'info>,
     /// The authority on the Token-2022 mint (must sign the CPI).
@@ -211,13 +857,86 @@ pub struct RegisterHook<'info> {
     pub token_program: Program<'info, Token2022>,
 }
 
+/// Runs while the mint is being created, before any campaign exists for it
+#[derive(Accounts)]
+pub struct InitializeHook<'info> {
+    /// The uninitialized Token-2022 mint account.
+    #[account(mut)]
+    ///CHECK: Token mint account
+    pub token_mint: AccountInfo<'info>,
+    /// Becomes the mint's transfer hook authority.
+    #[account(signer)]
+    ///CHECK: The authority who triggered the update
+    pub authority: AccountInfo<'info>,
+    /// The SPL Token-2022 program.
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeExtraAccountMetaList<'info> {
+    #[account(
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>, // Global state account
+    #[account(
+        seeds = [ROLES_SEED, global_state.key().as_ref()],
+        bump = roles.bump,
+        constraint = roles.config_admin == authority.key() @ ErrorCode::Unauthorized // Requires the config admin role
+    )]
+    pub roles: Account<'info, Roles>, // Role assignments for this campaign
+    #[account(mut)]
+    pub authority: Signer<'info>, // Admin authority, pays for the list
+    /// CHECK: TLV account read by Token-2022 on every transfer, written with spl-tlv-account-resolution
+    #[account(
+        init,
+        payer = authority,
+        space = ExtraAccountMetaList::size_of(EXTRA_ACCOUNT_METAS_COUNT)?,
+        seeds = [EXTRA_ACCOUNT_METAS_SEED, global_state.token_mint.as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateExtraAccountMetaList<'info> {
+    #[account(
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>, // Global state account
+    #[account(
+        seeds = [ROLES_SEED, global_state.key().as_ref()],
+        bump = roles.bump,
+        constraint = roles.config_admin == authority.key() @ ErrorCode::Unauthorized // Requires the config admin role
+    )]
+    pub roles: Account<'info, Roles>, // Role assignments for this campaign
+    pub authority: Signer<'info>, // Admin authority
+    /// CHECK: TLV account read by Token-2022 on every transfer, rewritten in place
+    #[account(
+        mut,
+        owner = crate::ID @ ErrorCode::InvalidExtraAccountMetaList,
+        seeds = [EXTRA_ACCOUNT_METAS_SEED, global_state.token_mint.as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: AccountInfo<'info>,
+}
+
 #[derive(Accounts)]
 pub struct UpdateRaydiumProgramId<'info> {
     #[account(
         mut,
-        has_one = authority @ ErrorCode::Unauthorized // Ensure authority matches the one in GlobalState
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
     )]
     pub global_state: Account<'info, GlobalState>, // Global state account
+    #[account(
+        seeds = [ROLES_SEED, global_state.key().as_ref()],
+        bump = roles.bump,
+        constraint = roles.config_admin == authority.key() @ ErrorCode::Unauthorized // Requires the config admin role
+    )]
+    pub roles: Account<'info, Roles>, // Role assignments for this campaign
     ///CHECK: This is a standard wallet account, and the program will verify its usage
     pub authority: Signer<'info>, // Admin authority
 }
@@ -226,9 +945,16 @@ pub struct UpdateRaydiumProgramId<'info> {
 pub struct UpdateLiquidityPoolAddress<'info> {
     #[account(
         mut,
-        has_one = authority @ ErrorCode::Unauthorized, // Check that the provided authority matches
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
     )]
     pub global_state: Account<'info, GlobalState>, // Global State
+    #[account(
+        seeds = [ROLES_SEED, global_state.key().as_ref()],
+        bump = roles.bump,
+        constraint = roles.config_admin == authority.key() @ ErrorCode::Unauthorized // Requires the config admin role
+    )]
+    pub roles: Account<'info, Roles>, // Role assignments for this campaign
     #[account(signer)]
     ///CHECK: The signer is the program's caller
     pub authority: Signer<'info>, // Admin signer to approve such changes
@@ -238,7 +964,9 @@ pub struct UpdateLiquidityPoolAddress<'info> {
 pub struct UpdateAuthority<'info> {
     #[account(
         mut,
-        has_one = authority @ ErrorCode::Unauthorized // Verify relationship
+        has_one = authority @ ErrorCode::Unauthorized, // Verify relationship
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
     )]
     pub global_state: Account<'info, GlobalState>, // Global State
     #[account(signer)]
@@ -246,24 +974,219 @@ pub struct UpdateAuthority<'info> {
     pub authority: AccountInfo<'info>, // Current authority
 }
 
-pub struct RaydiumTransactionHelper;
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        constraint = global_state.pending_authority != Pubkey::default() @ ErrorCode::NoPendingAuthority,
+        constraint = global_state.pending_authority == new_authority.key() @ ErrorCode::Unauthorized,
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>, // Global State
+    pub new_authority: Signer<'info>, // Proposed authority accepting the role
+}
 
-impl RaydiumTransactionHelper {
-    /// Checks if the source account belongs to Raydium's Program
-    pub fn is_raydium_transaction(source_account: &AccountInfo, raydium_program_id: Pubkey) -> bool {
-        // Check if the source account's owner matches Raydium's program ID
-        if source_account.owner == &raydium_program_id {
-            msg!("Raydium transaction detected for source account: {:?}", source_account.key);
-            return true;
-        }
+#[derive(Accounts)]
+pub struct ManageRoles<'info> {
+    #[account(
+        has_one = authority @ ErrorCode::Unauthorized, // Only the owner hands out roles
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>, // Global State
+    #[account(
+        mut,
+        seeds = [ROLES_SEED, global_state.key().as_ref()],
+        bump = roles.bump,
+    )]
+    pub roles: Account<'info, Roles>, // Role assignments for this campaign
+    pub authority: Signer<'info>, // Current authority
+}
+
+#[derive(Accounts)]
+pub struct CreateMultisig<'info> {
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized, // Only the owner can hand control to a multisig
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>, // Global State
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Multisig::LEN,
+        seeds = [MULTISIG_SEED, global_state.key().as_ref()],
+        bump
+    )]
+    pub multisig: Account<'info, Multisig>, // Signer set for this campaign
+    #[account(mut)]
+    pub authority: Signer<'info>, // Current authority, pays for the multisig account
+    pub system_program: Program<'info, System>,
+}
 
-        // Log for non-Raydium transactions (optional, for debugging)
-        msg!(
-            "Source account {:?} is not owned by Raydium. Owner: {:?}",
-            source_account.key,
-            source_account.owner
+#[derive(Accounts)]
+pub struct ProposeAdminAction<'info> {
+    #[account(
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>, // Global State
+    #[account(
+        mut,
+        seeds = [MULTISIG_SEED, global_state.key().as_ref()],
+        bump = multisig.bump,
+        constraint = multisig.signers.contains(&proposer.key()) @ ErrorCode::NotMultisigSigner
+    )]
+    pub multisig: Account<'info, Multisig>, // Signer set for this campaign
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + AdminProposal::LEN,
+        seeds = [PROPOSAL_SEED, multisig.key().as_ref(), multisig.proposal_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, AdminProposal>, // New proposal
+    #[account(mut)]
+    pub proposer: Signer<'info>, // Multisig signer, pays for the proposal account
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveAdminAction<'info> {
+    #[account(
+        seeds = [MULTISIG_SEED, multisig.global_state.as_ref()],
+        bump = multisig.bump,
+        constraint = multisig.signers.contains(&signer.key()) @ ErrorCode::NotMultisigSigner
+    )]
+    pub multisig: Account<'info, Multisig>, // Signer set for this campaign
+    #[account(
+        mut,
+        has_one = multisig @ ErrorCode::InvalidProposal,
+        seeds = [PROPOSAL_SEED, multisig.key().as_ref(), proposal.index.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, AdminProposal>, // Proposal being approved
+    pub signer: Signer<'info>, // Approving multisig signer
+}
+
+#[derive(Accounts)]
+pub struct ExecuteAdminAction<'info> {
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATE_SEED, global_state.token_mint.as_ref()], // Mint-seeded PDA
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>, // Global State
+    #[account(
+        mut,
+        seeds = [MULTISIG_SEED, global_state.key().as_ref()],
+        bump = multisig.bump,
+        constraint = multisig.signers.contains(&executor.key()) @ ErrorCode::NotMultisigSigner
+    )]
+    pub multisig: Account<'info, Multisig>, // Signer set for this campaign
+    #[account(
+        mut,
+        has_one = multisig @ ErrorCode::InvalidProposal,
+        seeds = [PROPOSAL_SEED, multisig.key().as_ref(), proposal.index.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, AdminProposal>, // Approved proposal
+    pub executor: Signer<'info>, // Any multisig signer
+}
+
+/// Aggregate price read from a Pyth v2 price account
+#[derive(Clone, Copy, Debug)]
+pub struct PythPrice {
+    pub price: i64,        // Aggregate price, scaled by 10^expo
+    pub conf: u64,         // Confidence interval, same scale as the price
+    pub expo: i32,         // Price exponent (usually negative)
+    pub publish_time: i64, // Unix timestamp of the last aggregate update
+}
+
+impl PythPrice {
+    pub const MAGIC: u32 = 0xa1b2_c3d4;
+    pub const VERSION: u32 = 2;
+    pub const ACCOUNT_TYPE_PRICE: u32 = 3;
+    pub const STATUS_TRADING: u32 = 1;
+
+    // Byte offsets inside the Pyth v2 price account layout
+    const MAGIC_OFFSET: usize = 0;
+    const VERSION_OFFSET: usize = 4;
+    const ACCOUNT_TYPE_OFFSET: usize = 8;
+    const EXPO_OFFSET: usize = 20;
+    const TIMESTAMP_OFFSET: usize = 96;
+    const AGG_PRICE_OFFSET: usize = 208;
+    const AGG_CONF_OFFSET: usize = 216;
+    const AGG_STATUS_OFFSET: usize = 224;
+    pub const MIN_LEN: usize = 240;
+
+    /// Parses the aggregate price, rejecting anything that is not a trading Pyth price account
+    pub fn load(data: &[u8]) -> Result<Self> {
+        require!(data.len() >= Self::MIN_LEN, ErrorCode::InvalidPriceFeed);
+
+        let read_u32 = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+        let read_u64 = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+
+        require!(
+            read_u32(Self::MAGIC_OFFSET) == Self::MAGIC
+                && read_u32(Self::VERSION_OFFSET) == Self::VERSION
+                && read_u32(Self::ACCOUNT_TYPE_OFFSET) == Self::ACCOUNT_TYPE_PRICE,
+            ErrorCode::InvalidPriceFeed
+        );
+        require!(
+            read_u32(Self::AGG_STATUS_OFFSET) == Self::STATUS_TRADING,
+            ErrorCode::PriceUnavailable
         );
 
-        return false;
+        Ok(Self {
+            price: read_u64(Self::AGG_PRICE_OFFSET) as i64,
+            conf: read_u64(Self::AGG_CONF_OFFSET),
+            expo: read_u32(Self::EXPO_OFFSET) as i32,
+            publish_time: read_u64(Self::TIMESTAMP_OFFSET) as i64,
+        })
+    }
+}
+
+/// Fields read from a Raydium AMM v4 pool (`AmmInfo`) account
+#[derive(Clone, Copy, Debug)]
+pub struct RaydiumAmmPool {
+    pub coin_decimals: u8,      // Decimals of the base (coin) side
+    pub pc_decimals: u8,        // Decimals of the quote (pc) side
+    pub coin_vault: Pubkey,     // Token account holding the base reserve
+    pub pc_vault: Pubkey,       // Token account holding the quote reserve
+    pub coin_vault_mint: Pubkey,
+    pub pc_vault_mint: Pubkey,
+}
+
+impl RaydiumAmmPool {
+    pub const LEN: usize = 752;
+
+    // Byte offsets inside the Raydium AMM v4 `AmmInfo` layout
+    const COIN_DECIMALS_OFFSET: usize = 32;
+    const PC_DECIMALS_OFFSET: usize = 40;
+    const COIN_VAULT_OFFSET: usize = 336;
+    const PC_VAULT_OFFSET: usize = 368;
+    const COIN_VAULT_MINT_OFFSET: usize = 400;
+    const PC_VAULT_MINT_OFFSET: usize = 432;
+
+    /// Parses the pool fields needed to price the token from its reserves
+    pub fn load(data: &[u8]) -> Result<Self> {
+        require!(data.len() == Self::LEN, ErrorCode::InvalidLiquidityPool);
+
+        let read_u64 = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+        let read_pubkey = |offset: usize| Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap());
+
+        Ok(Self {
+            coin_decimals: u8::try_from(read_u64(Self::COIN_DECIMALS_OFFSET))
+                .map_err(|_| ErrorCode::InvalidLiquidityPool)?,
+            pc_decimals: u8::try_from(read_u64(Self::PC_DECIMALS_OFFSET))
+                .map_err(|_| ErrorCode::InvalidLiquidityPool)?,
+            coin_vault: read_pubkey(Self::COIN_VAULT_OFFSET),
+            pc_vault: read_pubkey(Self::PC_VAULT_OFFSET),
+            coin_vault_mint: read_pubkey(Self::COIN_VAULT_MINT_OFFSET),
+            pc_vault_mint: read_pubkey(Self::PC_VAULT_MINT_OFFSET),
+        })
     }
 }
\ No newline at end of file
@@ -0,0 +1,84 @@
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::CampaignBuilder;
+use hotwings::structs::GlobalState;
+use solana_program::instruction::Instruction;
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+fn update_authority_ix(global_state: Pubkey, authority: Pubkey, data: Vec<u8>) -> Instruction {
+    Instruction {
+        program_id: hotwings::id(),
+        accounts: hotwings::accounts::UpdateAuthority { global_state, authority }.to_account_metas(None),
+        data,
+    }
+}
+
+fn accept_ix(global_state: Pubkey, new_authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: hotwings::id(),
+        accounts: hotwings::accounts::AcceptAuthority { global_state, new_authority }.to_account_metas(None),
+        data: hotwings::instruction::AcceptAuthority {}.data(),
+    }
+}
+
+#[tokio::test]
+async fn test_authority_transfer_needs_acceptance() {
+    let mut campaign = CampaignBuilder::new(GlobalState::default()).start().await;
+    let (global_state, authority) = (campaign.global_state, campaign.admin.insecure_clone());
+    let new_authority = Keypair::new();
+    let impostor = Keypair::new();
+
+    // Proposing leaves the current authority in place
+    let ix = update_authority_ix(
+        global_state,
+        authority.pubkey(),
+        hotwings::instruction::ProposeAuthority { new_authority: new_authority.pubkey() }.data(),
+    );
+    campaign.send(&[ix], &[&authority]).await.unwrap();
+    let state = campaign.load_state().await;
+    assert_eq!(state.authority, authority.pubkey());
+    assert_eq!(state.pending_authority, new_authority.pubkey());
+
+    // Only the proposed key can accept
+    assert!(campaign.send(&[accept_ix(global_state, impostor.pubkey())], &[&impostor]).await.is_err());
+
+    campaign.send(&[accept_ix(global_state, new_authority.pubkey())], &[&new_authority]).await.unwrap();
+    let state = campaign.load_state().await;
+    assert_eq!(state.authority, new_authority.pubkey());
+    assert_eq!(state.pending_authority, Pubkey::default());
+
+    // A cancelled proposal can no longer be accepted
+    let ix = update_authority_ix(
+        global_state,
+        new_authority.pubkey(),
+        hotwings::instruction::ProposeAuthority { new_authority: impostor.pubkey() }.data(),
+    );
+    campaign.send(&[ix], &[&new_authority]).await.unwrap();
+    let ix = update_authority_ix(
+        global_state,
+        new_authority.pubkey(),
+        hotwings::instruction::CancelAuthorityTransfer {}.data(),
+    );
+    campaign.send(&[ix], &[&new_authority]).await.unwrap();
+    assert!(campaign.send(&[accept_ix(global_state, impostor.pubkey())], &[&impostor]).await.is_err());
+    assert_eq!(campaign.load_state().await.authority, new_authority.pubkey());
+}
+
+#[tokio::test]
+async fn test_global_state_must_be_mint_pda() {
+    // Program-owned copy of a valid config at an arbitrary address
+    let mut builder = CampaignBuilder::new(GlobalState::default());
+    let global_state = Pubkey::new_unique();
+    builder.program_test.add_account(global_state, common::global_state_account(&builder.state));
+    let mut campaign = builder.start().await;
+    let authority = campaign.admin.insecure_clone();
+
+    let ix = update_authority_ix(
+        global_state,
+        authority.pubkey(),
+        hotwings::instruction::ProposeAuthority { new_authority: Pubkey::new_unique() }.data(),
+    );
+    assert!(campaign.send(&[ix], &[&authority]).await.is_err());
+}
@@ -0,0 +1,223 @@
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{Campaign, CampaignBuilder};
+use hotwings::consts::{MAX_MILESTONES, RAYDIUM_SWAP_BASE_IN_TAG};
+use hotwings::errors::ErrorCode;
+use hotwings::structs::{GlobalState, HoldCapMode, Milestone, Roles};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{Instruction, InstructionError},
+    program::invoke,
+    program_error::ProgramError,
+};
+use solana_program_test::{processor, tokio, BanksClientError};
+use solana_sdk::{account::Account, pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::TransactionError};
+use spl_associated_token_account::get_associated_token_address;
+
+const VAULT_BALANCE: u64 = 1_000_000;
+/// Locked for each registered user
+const ALLOCATION: u64 = 400_000;
+const USER_COUNT: usize = 2;
+const CRANK_REWARD_POOL: u64 = 100_000;
+/// Vault balance owed to neither the users nor the crank reward pool
+const SURPLUS: u64 = VAULT_BALANCE - USER_COUNT as u64 * ALLOCATION - CRANK_REWARD_POOL;
+
+/// Mint of the token the vault is sold for
+const QUOTE_MINT: Pubkey = Pubkey::new_from_array([9; 32]);
+
+/// Stand-in for Raydium that only accepts a `swap_base_in` signed by the vault owner, moving
+/// `amount_in` from the source into the pool's coin account
+fn raydium_stand_in(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    if data.len() != 17 || data[0] != RAYDIUM_SWAP_BASE_IN_TAG || accounts.len() != 18 || !accounts[17].is_signer {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let amount_in = u64::from_le_bytes(data[1..9].try_into().unwrap());
+    let (token_program, pool_coin, source, owner) = (&accounts[0], &accounts[5], &accounts[15], &accounts[17]);
+    invoke(
+        &spl_token::instruction::transfer(token_program.key, source.key, pool_coin.key, owner.key, &[], amount_in)?,
+        &[source.clone(), pool_coin.clone(), owner.clone(), token_program.clone()],
+    )
+}
+
+/// Campaign over a funded vault with every milestone reached and a Raydium stand-in pool,
+/// returning it with its treasury signer
+async fn setup(unlock_complete: bool) -> (Campaign, Keypair) {
+    let raydium_program_id = Pubkey::new_unique();
+    let mut milestones = [Milestone::default(); MAX_MILESTONES];
+    milestones[0] = Milestone { market_cap: 1, unlock_percent: 100, sustain_seconds: 0, hold_cap: 0 };
+    let mut builder = CampaignBuilder::new(GlobalState {
+        project_wallet: Pubkey::new_unique(),
+        burn_wallet: Pubkey::new_unique(),
+        marketing_wallet: Pubkey::new_unique(),
+        raydium_program_id,
+        liquidity_pool: Pubkey::new_unique(),
+        milestones,
+        milestone_count: 1,
+        current_milestone: 1,
+        unlock_complete,
+        hold_cap_mode: HoldCapMode::Absolute,
+        hold_cap: u64::MAX,
+        crank_reward_pool: CRANK_REWARD_POOL,
+        ..GlobalState::default()
+    });
+    builder.program_test.add_program("raydium_stand_in", raydium_program_id, processor!(raydium_stand_in));
+    builder.fund_vault(VAULT_BALANCE);
+    for owner in [builder.state.burn_wallet, builder.state.marketing_wallet] {
+        let ata = get_associated_token_address(&owner, &builder.mint.pubkey());
+        builder.add_token_account(ata, owner, 0);
+    }
+
+    // The sale needs its own role, away from the config admin
+    let treasury_signer = Keypair::new();
+    builder.roles = Roles { treasury_signer: treasury_signer.pubkey(), ..builder.roles };
+
+    let project_wallet = builder.state.project_wallet;
+    builder.program_test.add_account(
+        get_associated_token_address(&project_wallet, &QUOTE_MINT),
+        common::token_program_account(spl_token::id(), common::token_account_data(QUOTE_MINT, project_wallet, 0)),
+    );
+    builder.program_test.add_account(
+        builder.state.liquidity_pool,
+        Account { lamports: 1_000_000_000, data: vec![0; 8], owner: raydium_program_id, ..Account::default() },
+    );
+    let pool_coin = pool_coin_token_account(&builder.state.liquidity_pool);
+    builder.add_token_account(pool_coin, Pubkey::new_unique(), 0);
+    (builder.start().await, treasury_signer)
+}
+
+/// Pool account receiving the sold vault tokens
+fn pool_coin_token_account(liquidity_pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"coin", liquidity_pool.as_ref()], &spl_token::id()).0
+}
+
+/// Registers `USER_COUNT` wallets for `ALLOCATION` each, returning them with their ATAs
+async fn register_users(campaign: &mut Campaign) -> Vec<(Keypair, Pubkey)> {
+    let mut users = Vec::new();
+    for _ in 0..USER_COUNT {
+        let wallet = Keypair::new();
+        let ata = campaign.ata(&wallet.pubkey());
+        campaign.register_users(&[wallet.pubkey()], &[ata], ALLOCATION).await.unwrap();
+        users.push((wallet, ata));
+    }
+    users
+}
+
+async fn claim(campaign: &mut Campaign, wallet: &Keypair, ata: Pubkey) -> Result<(), BanksClientError> {
+    let state = campaign.load_state().await;
+    let ix = Instruction {
+        program_id: hotwings::id(),
+        accounts: hotwings::accounts::Claim {
+            global_state: campaign.global_state,
+            user: wallet.pubkey(),
+            user_state: common::user_state_address(&campaign.global_state, &wallet.pubkey()),
+            user_ata: ata,
+            vault: campaign.vault,
+            vault_authority: campaign.vault_authority,
+            burn_wallet: campaign.ata(&state.burn_wallet),
+            marketing_wallet: campaign.ata(&state.marketing_wallet),
+            token_mint: campaign.token_mint,
+            treasury_wallet: None,
+            token_program: spl_token::id(),
+        }
+        .to_account_metas(None),
+        data: hotwings::instruction::Claim {}.data(),
+    };
+    campaign.send(&[ix], &[wallet]).await
+}
+
+async fn auto_sell(campaign: &mut Campaign, signer: &Keypair) -> Result<(), BanksClientError> {
+    let state = campaign.load_state().await;
+    let accounts = hotwings::accounts::AutoSell {
+        global_state: campaign.global_state,
+        roles: campaign.roles,
+        authority: signer.pubkey(),
+        vault: campaign.vault,
+        vault_authority: campaign.vault_authority,
+        quote_destination: get_associated_token_address(&state.project_wallet, &QUOTE_MINT),
+        raydium_program: state.raydium_program_id,
+        amm: state.liquidity_pool,
+        amm_authority: Pubkey::new_unique(),
+        amm_open_orders: Pubkey::new_unique(),
+        amm_target_orders: Pubkey::new_unique(),
+        pool_coin_token_account: pool_coin_token_account(&state.liquidity_pool),
+        pool_pc_token_account: Pubkey::new_unique(),
+        serum_program: Pubkey::new_unique(),
+        serum_market: Pubkey::new_unique(),
+        serum_bids: Pubkey::new_unique(),
+        serum_asks: Pubkey::new_unique(),
+        serum_event_queue: Pubkey::new_unique(),
+        serum_coin_vault: Pubkey::new_unique(),
+        serum_pc_vault: Pubkey::new_unique(),
+        serum_vault_signer: Pubkey::new_unique(),
+        token_program: spl_token::id(),
+    };
+    let ix = Instruction {
+        program_id: hotwings::id(),
+        accounts: accounts.to_account_metas(None),
+        data: hotwings::instruction::AutoSell { minimum_amount_out: 1 }.data(),
+    };
+    campaign.send(&[ix], &[signer]).await
+}
+
+#[tokio::test]
+async fn test_auto_sell_swaps_a_quarter_of_the_surplus() {
+    let (mut campaign, treasury_signer) = setup(true).await;
+    register_users(&mut campaign).await;
+    assert_eq!(campaign.load_state().await.outstanding_allocations, USER_COUNT as u64 * ALLOCATION);
+
+    auto_sell(&mut campaign, &treasury_signer).await.unwrap();
+
+    let state = campaign.load_state().await;
+    let pool_coin = pool_coin_token_account(&state.liquidity_pool);
+    assert_eq!(campaign.balance(pool_coin).await, Some(SURPLUS / 4));
+    assert_eq!(campaign.balance(campaign.vault).await, Some(VAULT_BALANCE - SURPLUS / 4));
+    assert!(state.auto_sold);
+}
+
+#[tokio::test]
+async fn test_auto_sell_runs_once() {
+    let (mut campaign, treasury_signer) = setup(true).await;
+    auto_sell(&mut campaign, &treasury_signer).await.unwrap();
+
+    let err = auto_sell(&mut campaign, &treasury_signer).await.unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ErrorCode::AutoSellAlreadyExecuted.into()))
+    );
+}
+
+#[tokio::test]
+async fn test_users_claim_in_full_after_auto_sell() {
+    let (mut campaign, treasury_signer) = setup(true).await;
+    let users = register_users(&mut campaign).await;
+
+    auto_sell(&mut campaign, &treasury_signer).await.unwrap();
+
+    for (wallet, ata) in &users {
+        claim(&mut campaign, wallet, *ata).await.unwrap();
+        assert_eq!(campaign.balance(*ata).await, Some(ALLOCATION));
+    }
+    let state = campaign.load_state().await;
+    assert_eq!(state.outstanding_allocations, 0);
+    // What is left still covers the crank rewards
+    assert_eq!(campaign.balance(campaign.vault).await, Some(CRANK_REWARD_POOL + SURPLUS - SURPLUS / 4));
+}
+
+#[tokio::test]
+async fn test_auto_sell_needs_the_treasury_signer() {
+    let (mut campaign, _) = setup(true).await;
+    let admin = campaign.admin.insecure_clone();
+
+    assert!(auto_sell(&mut campaign, &Keypair::new()).await.is_err());
+    // Other roles do not carry the right to sell
+    assert!(auto_sell(&mut campaign, &admin).await.is_err());
+}
+
+#[tokio::test]
+async fn test_auto_sell_waits_for_full_unlock() {
+    let (mut campaign, treasury_signer) = setup(false).await;
+
+    assert!(auto_sell(&mut campaign, &treasury_signer).await.is_err());
+}
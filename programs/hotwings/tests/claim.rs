@@ -0,0 +1,315 @@
+mod common;
+
+use anchor_lang::{AccountSerialize, InstructionData, ToAccountMetas};
+use common::{Campaign, CampaignBuilder};
+use hotwings::consts::MAX_MILESTONES;
+use hotwings::structs::{BurnMode, GlobalState, HoldCapMode, Milestone, MilestoneUnlockAccount};
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use spl_associated_token_account::get_associated_token_address;
+
+const DECIMALS: u8 = 6;
+const ALLOCATION: u64 = 1_000_000;
+const VAULT_BALANCE: u64 = 10 * ALLOCATION;
+
+struct User {
+    wallet: Keypair,
+    user_state: Pubkey,
+    ata: Pubkey,
+}
+
+/// Token accounts receiving the burn, marketing and treasury shares of the tax
+struct TaxWallets {
+    burn: Pubkey,
+    marketing: Pubkey,
+    treasury: Pubkey,
+}
+
+/// Campaign state with a 1% tax split evenly between burn and marketing and no hold cap
+fn campaign(milestones: &[Milestone], current_milestone: u8) -> GlobalState {
+    let mut schedule = [Milestone::default(); MAX_MILESTONES];
+    schedule[..milestones.len()].copy_from_slice(milestones);
+    GlobalState {
+        burn_wallet: Pubkey::new_unique(),
+        marketing_wallet: Pubkey::new_unique(),
+        treasury_wallet: Pubkey::new_unique(),
+        milestones: schedule,
+        milestone_count: milestones.len() as u8,
+        current_milestone,
+        tax_bps: 100,
+        burn_share_bps: 5_000,
+        marketing_share_bps: 5_000,
+        hold_cap_mode: HoldCapMode::Absolute,
+        hold_cap: u64::MAX,
+        token_decimals: DECIMALS,
+        ..GlobalState::default()
+    }
+}
+
+fn milestone(market_cap: u64, unlock_percent: u8) -> Milestone {
+    Milestone { market_cap, unlock_percent, sustain_seconds: 0, hold_cap: 0 }
+}
+
+/// Deploys `state` with a funded vault and one registered user per entry of `users`, each given
+/// as (user state, starting ATA balance)
+async fn setup(state: GlobalState, users: Vec<(MilestoneUnlockAccount, u64)>) -> (Campaign, Vec<User>) {
+    let mut builder = CampaignBuilder::new(state);
+    builder.fund_vault(VAULT_BALANCE);
+    for owner in [builder.state.burn_wallet, builder.state.marketing_wallet, builder.state.treasury_wallet] {
+        let ata = get_associated_token_address(&owner, &builder.mint.pubkey());
+        builder.add_token_account(ata, owner, 0);
+    }
+
+    let global_state = builder.global_state();
+    let mut registered = Vec::new();
+    for (mut user_state, balance) in users {
+        let wallet = builder.add_wallet();
+        let ata = get_associated_token_address(&wallet.pubkey(), &builder.mint.pubkey());
+        let address = common::user_state_address(&global_state, &wallet.pubkey());
+        user_state.wallet = wallet.pubkey();
+        user_state.ata = ata;
+        let mut data = Vec::new();
+        user_state.try_serialize(&mut data).unwrap();
+        builder.program_test.add_account(address, common::program_account(data));
+        builder.add_token_account(ata, wallet.pubkey(), balance);
+        builder.state.outstanding_allocations += user_state.total_locked_tokens;
+        registered.push(User { wallet, user_state: address, ata });
+    }
+    (builder.start().await, registered)
+}
+
+async fn tax_wallets(campaign: &mut Campaign) -> TaxWallets {
+    let state = campaign.load_state().await;
+    TaxWallets {
+        burn: campaign.ata(&state.burn_wallet),
+        marketing: campaign.ata(&state.marketing_wallet),
+        treasury: campaign.ata(&state.treasury_wallet),
+    }
+}
+
+/// User state for a fresh registration of `ALLOCATION` tokens
+fn registered_user() -> MilestoneUnlockAccount {
+    MilestoneUnlockAccount {
+        wallet: Pubkey::default(),
+        ata: Pubkey::default(),
+        allocated_tokens: ALLOCATION,
+        total_locked_tokens: ALLOCATION,
+        unlocked_tokens: 0,
+        deferred_tokens: 0,
+        last_unlocked_milestone: 0,
+    }
+}
+
+async fn claim(campaign: &mut Campaign, user: &User) -> Result<(), BanksClientError> {
+    let tax_wallets = tax_wallets(campaign).await;
+    let ix = Instruction {
+        program_id: hotwings::id(),
+        accounts: hotwings::accounts::Claim {
+            global_state: campaign.global_state,
+            user: user.wallet.pubkey(),
+            user_state: user.user_state,
+            user_ata: user.ata,
+            vault: campaign.vault,
+            vault_authority: campaign.vault_authority,
+            burn_wallet: tax_wallets.burn,
+            marketing_wallet: tax_wallets.marketing,
+            token_mint: campaign.token_mint,
+            treasury_wallet: Some(tax_wallets.treasury),
+            token_program: spl_token::id(),
+        }
+        .to_account_metas(None),
+        data: hotwings::instruction::Claim {}.data(),
+    };
+    campaign.send(&[ix], &[&user.wallet]).await
+}
+
+/// Pushes unlocks to `users` from the `signer` holding, or not, the config admin role
+async fn push_unlocks(campaign: &mut Campaign, users: &[&User], signer: &Keypair) -> Result<(), BanksClientError> {
+    let tax_wallets = tax_wallets(campaign).await;
+    let mut accounts = hotwings::accounts::PushUnlocks {
+        global_state: campaign.global_state,
+        roles: campaign.roles,
+        authority: signer.pubkey(),
+        vault: campaign.vault,
+        vault_authority: campaign.vault_authority,
+        burn_wallet: tax_wallets.burn,
+        marketing_wallet: tax_wallets.marketing,
+        token_mint: campaign.token_mint,
+        treasury_wallet: Some(tax_wallets.treasury),
+        token_program: spl_token::id(),
+    }
+    .to_account_metas(None);
+    for user in users {
+        accounts.push(AccountMeta::new(user.user_state, false));
+        accounts.push(AccountMeta::new(user.ata, false));
+    }
+    let ix = Instruction { program_id: hotwings::id(), accounts, data: hotwings::instruction::PushUnlocks {}.data() };
+    campaign.send(&[ix], &[signer]).await
+}
+
+async fn balance(campaign: &mut Campaign, token_account: Pubkey) -> u64 {
+    campaign.balance(token_account).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_burn_mode_burns_from_the_vault() {
+    let state = GlobalState { burn_mode: BurnMode::Burn, ..campaign(&[milestone(1, 100)], 1) };
+    let (mut campaign, users) = setup(state, vec![(registered_user(), 0)]).await;
+    let supply_before = campaign.supply().await;
+
+    claim(&mut campaign, &users[0]).await.unwrap();
+
+    // 1% tax, half of it burned
+    let burned = ALLOCATION / 100 / 2;
+    let tax_wallets = tax_wallets(&mut campaign).await;
+    assert_eq!(campaign.supply().await, supply_before - burned);
+    assert_eq!(balance(&mut campaign, tax_wallets.burn).await, 0);
+    assert_eq!(campaign.load_state().await.total_burned, burned);
+}
+
+#[tokio::test]
+async fn test_burn_wallet_mode_is_not_counted_as_burned() {
+    let state = GlobalState { burn_mode: BurnMode::TransferToBurnWallet, ..campaign(&[milestone(1, 100)], 1) };
+    let (mut campaign, users) = setup(state, vec![(registered_user(), 0)]).await;
+    let supply_before = campaign.supply().await;
+
+    claim(&mut campaign, &users[0]).await.unwrap();
+
+    let tax_wallets = tax_wallets(&mut campaign).await;
+    assert_eq!(campaign.supply().await, supply_before);
+    assert_eq!(balance(&mut campaign, tax_wallets.burn).await, ALLOCATION / 100 / 2);
+    assert_eq!(campaign.load_state().await.total_burned, 0);
+}
+
+#[tokio::test]
+async fn test_tax_is_split_between_burn_marketing_and_treasury() {
+    let state = GlobalState {
+        tax_bps: 1_000,
+        burn_share_bps: 5_000,
+        marketing_share_bps: 3_000,
+        treasury_share_bps: 2_000,
+        ..campaign(&[milestone(1, 100)], 1)
+    };
+    let (mut campaign, users) = setup(state, vec![(registered_user(), 0)]).await;
+
+    claim(&mut campaign, &users[0]).await.unwrap();
+
+    // 10% tax on the allocation: half burned, 30% to marketing, 20% to the treasury
+    let tax = ALLOCATION / 10;
+    let tax_wallets = tax_wallets(&mut campaign).await;
+    assert_eq!(balance(&mut campaign, users[0].ata).await, ALLOCATION - tax);
+    assert_eq!(balance(&mut campaign, tax_wallets.burn).await, tax / 2);
+    assert_eq!(balance(&mut campaign, tax_wallets.marketing).await, tax * 3 / 10);
+    assert_eq!(balance(&mut campaign, tax_wallets.treasury).await, tax / 5);
+    let vault = campaign.vault;
+    assert_eq!(balance(&mut campaign, vault).await, VAULT_BALANCE - ALLOCATION);
+}
+
+#[tokio::test]
+async fn test_claim_pays_once_per_milestone() {
+    let (mut campaign, users) =
+        setup(campaign(&[milestone(1, 40), milestone(2, 100)], 1), vec![(registered_user(), 0)]).await;
+
+    claim(&mut campaign, &users[0]).await.unwrap();
+
+    let unlocked = ALLOCATION * 40 / 100;
+    assert_eq!(balance(&mut campaign, users[0].ata).await, unlocked - unlocked / 100);
+    let user_state: MilestoneUnlockAccount = campaign.load(users[0].user_state).await;
+    assert_eq!(user_state.unlocked_tokens, unlocked);
+    assert_eq!(user_state.total_locked_tokens, ALLOCATION - unlocked);
+    assert_eq!(user_state.last_unlocked_milestone, 1);
+
+    // Nothing more until the next milestone
+    assert!(claim(&mut campaign, &users[0]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_push_unlocks_pays_every_listed_user() {
+    let users = vec![(registered_user(), 0), (registered_user(), 0)];
+    let (mut campaign, users) = setup(campaign(&[milestone(1, 40), milestone(2, 100)], 1), users).await;
+    let admin = campaign.admin.insecure_clone();
+
+    push_unlocks(&mut campaign, &[&users[0], &users[1]], &admin).await.unwrap();
+
+    let unlocked = ALLOCATION * 40 / 100;
+    for user in &users {
+        assert_eq!(balance(&mut campaign, user.ata).await, unlocked - unlocked / 100);
+        let user_state: MilestoneUnlockAccount = campaign.load(user.user_state).await;
+        assert_eq!(user_state.unlocked_tokens, unlocked);
+        assert_eq!(user_state.last_unlocked_milestone, 1);
+    }
+
+    // Users already paid for this milestone are skipped, not paid twice
+    push_unlocks(&mut campaign, &[&users[0], &users[1]], &admin).await.unwrap();
+    assert_eq!(balance(&mut campaign, users[0].ata).await, unlocked - unlocked / 100);
+}
+
+#[tokio::test]
+async fn test_push_unlocks_needs_the_config_admin() {
+    let (mut campaign, users) = setup(campaign(&[milestone(1, 100)], 1), vec![(registered_user(), 0)]).await;
+
+    assert!(push_unlocks(&mut campaign, &[&users[0]], &Keypair::new()).await.is_err());
+
+    let user_state: MilestoneUnlockAccount = campaign.load(users[0].user_state).await;
+    assert_eq!(user_state.last_unlocked_milestone, 0);
+}
+
+#[tokio::test]
+async fn test_claim_catches_up_on_skipped_milestones() {
+    let milestones = [milestone(1, 10), milestone(2, 30), milestone(3, 60), milestone(4, 100)];
+    // Claimed at the first milestone, then sat out the second
+    let caught_up = MilestoneUnlockAccount {
+        total_locked_tokens: ALLOCATION - ALLOCATION / 10,
+        unlocked_tokens: ALLOCATION / 10,
+        last_unlocked_milestone: 1,
+        ..registered_user()
+    };
+    let (mut campaign, users) = setup(campaign(&milestones, 3), vec![(caught_up, 0), (registered_user(), 0)]).await;
+
+    claim(&mut campaign, &users[0]).await.unwrap();
+    claim(&mut campaign, &users[1]).await.unwrap();
+
+    // Both end up at the cumulative 60%, whatever they claimed before
+    for user in &users {
+        let user_state: MilestoneUnlockAccount = campaign.load(user.user_state).await;
+        assert_eq!(user_state.unlocked_tokens, ALLOCATION * 60 / 100);
+        assert_eq!(user_state.total_locked_tokens, ALLOCATION * 40 / 100);
+        assert_eq!(user_state.last_unlocked_milestone, 3);
+    }
+    let first_unlock = ALLOCATION * 50 / 100;
+    let second_unlock = ALLOCATION * 60 / 100;
+    assert_eq!(balance(&mut campaign, users[0].ata).await, first_unlock - first_unlock / 100);
+    assert_eq!(balance(&mut campaign, users[1].ata).await, second_unlock - second_unlock / 100);
+}
+
+#[tokio::test]
+async fn test_capped_unlock_is_deferred_then_released() {
+    const HOLD_CAP: u64 = ALLOCATION / 2;
+    const STARTING_BALANCE: u64 = 300_000;
+    let state = GlobalState { hold_cap: HOLD_CAP, ..campaign(&[milestone(1, 100)], 1) };
+    let (mut campaign, users) = setup(state, vec![(registered_user(), STARTING_BALANCE)]).await;
+    let user = &users[0];
+
+    // Only enough to reach the cap goes out, the rest stays owed
+    claim(&mut campaign, user).await.unwrap();
+    let first_unlock = HOLD_CAP - STARTING_BALANCE;
+    let user_state: MilestoneUnlockAccount = campaign.load(user.user_state).await;
+    assert_eq!(user_state.unlocked_tokens, first_unlock);
+    assert_eq!(user_state.deferred_tokens, ALLOCATION - first_unlock);
+
+    // The holder moves their tokens elsewhere, freeing room under the cap
+    let held = balance(&mut campaign, user.ata).await;
+    let burn_wallet = tax_wallets(&mut campaign).await.burn;
+    let ix = spl_token::instruction::transfer(&spl_token::id(), &user.ata, &burn_wallet, &user.wallet.pubkey(), &[], held)
+        .unwrap();
+    campaign.send(&[ix], &[&user.wallet]).await.unwrap();
+
+    // No new milestone, but the deferred tokens can now be claimed up to the cap
+    claim(&mut campaign, user).await.unwrap();
+    let user_state: MilestoneUnlockAccount = campaign.load(user.user_state).await;
+    assert_eq!(user_state.unlocked_tokens, first_unlock + HOLD_CAP);
+    assert_eq!(user_state.deferred_tokens, ALLOCATION - first_unlock - HOLD_CAP);
+    assert_eq!(user_state.total_locked_tokens, ALLOCATION - first_unlock - HOLD_CAP);
+    assert_eq!(balance(&mut campaign, user.ata).await, HOLD_CAP - HOLD_CAP / 100);
+}
@@ -0,0 +1,382 @@
+// Fixtures shared by the program tests; each test binary only uses some of them
+#![allow(dead_code)]
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use hotwings::consts::{GLOBAL_STATE_SEED, ROLES_SEED, USER_STATE_SEED, VAULT_AUTHORITY_SEED};
+use hotwings::structs::{GlobalState, Roles, UserEntry};
+use solana_program::{
+    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, instruction::{AccountMeta, Instruction},
+    program_option::COption, program_pack::Pack, system_instruction,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{account::Account, pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction};
+use spl_associated_token_account::{get_associated_token_address_with_program_id, instruction::create_associated_token_account};
+use spl_token_2022::extension::{transfer_hook, ExtensionType};
+
+// Anchor's entrypoint wants the account slice to live as long as the accounts themselves
+pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    hotwings::entry(program_id, accounts, data)
+}
+
+pub fn program_test() -> ProgramTest {
+    ProgramTest::new("hotwings", hotwings::id(), processor!(process_instruction))
+}
+
+pub fn global_state_address(token_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[GLOBAL_STATE_SEED, token_mint.as_ref()], &hotwings::id())
+}
+
+pub fn roles_address(global_state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ROLES_SEED, global_state.as_ref()], &hotwings::id())
+}
+
+/// Account owned by this program holding `data`
+pub fn program_account(data: Vec<u8>) -> Account {
+    Account { lamports: 1_000_000_000, data, owner: hotwings::id(), ..Account::default() }
+}
+
+/// Serialized `GlobalState`, padded to the size `initialize_program` allocates
+pub fn global_state_account(state: &GlobalState) -> Account {
+    let mut data = Vec::new();
+    state.try_serialize(&mut data).unwrap();
+    data.resize(8 + GlobalState::LEN, 0);
+    program_account(data)
+}
+
+pub fn roles_account(roles: &Roles) -> Account {
+    let mut data = Vec::new();
+    roles.try_serialize(&mut data).unwrap();
+    program_account(data)
+}
+
+/// Roles account where `holder` holds every role
+pub fn all_roles(global_state: Pubkey, holder: Pubkey) -> Roles {
+    Roles {
+        global_state,
+        config_admin: holder,
+        oracle_updater: holder,
+        exemptions_manager: holder,
+        treasury_signer: holder,
+        bump: roles_address(&global_state).1,
+    }
+}
+
+/// Initialized mint without authorities; SPL Token and Token-2022 share the base layout
+pub fn mint_data(supply: u64, decimals: u8) -> Vec<u8> {
+    mint_data_with_authority(COption::None, supply, decimals)
+}
+
+pub fn mint_data_with_authority(mint_authority: COption<Pubkey>, supply: u64, decimals: u8) -> Vec<u8> {
+    let mut data = vec![0u8; spl_token_2022::state::Mint::LEN];
+    spl_token_2022::state::Mint {
+        mint_authority,
+        supply,
+        decimals,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    data
+}
+
+pub fn token_account_data(mint: Pubkey, owner: Pubkey, amount: u64) -> Vec<u8> {
+    let mut data = vec![0u8; spl_token_2022::state::Account::LEN];
+    spl_token_2022::state::Account {
+        mint,
+        owner,
+        amount,
+        state: spl_token_2022::state::AccountState::Initialized,
+        ..spl_token_2022::state::Account::default()
+    }
+    .pack_into_slice(&mut data);
+    data
+}
+
+/// Account owned by `token_program` holding `data`
+pub fn token_program_account(token_program: Pubkey, data: Vec<u8>) -> Account {
+    Account { lamports: 1_000_000_000, data, owner: token_program, ..Account::default() }
+}
+
+pub fn vault_authority_address(global_state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_AUTHORITY_SEED, global_state.as_ref()], &hotwings::id())
+}
+
+pub fn user_state_address(global_state: &Pubkey, wallet: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[USER_STATE_SEED, global_state.as_ref(), wallet.as_ref()], &hotwings::id()).0
+}
+
+/// System account with enough lamports to pay for its own transactions and rent
+pub fn funded_account() -> Account {
+    Account { lamports: 1_000_000_000, ..Account::default() }
+}
+
+/// Campaign being assembled before the test validator starts
+pub struct CampaignBuilder {
+    pub program_test: ProgramTest,
+    pub state: GlobalState,
+    pub roles: Roles,
+    /// Holds every role and the authority unless `roles` or `state` say otherwise
+    pub admin: Keypair,
+    pub mint: Keypair,
+    pub token_program: Pubkey,
+    /// Supply written to the legacy mint; grows with every token account added
+    pub supply: u64,
+}
+
+impl CampaignBuilder {
+    /// Campaign over a legacy SPL mint whose PDAs, vault and authority are filled into `state`
+    pub fn new(mut state: GlobalState) -> Self {
+        let admin = Keypair::new();
+        let mint = Keypair::new();
+        let (global_state, bump) = global_state_address(&mint.pubkey());
+        let (_, vault_authority_bump) = vault_authority_address(&global_state);
+        state.authority = admin.pubkey();
+        state.token_mint = mint.pubkey();
+        state.vault = Pubkey::new_unique();
+        state.vault_authority_bump = vault_authority_bump;
+        state.bump = bump;
+
+        let mut program_test = program_test();
+        program_test.add_account(admin.pubkey(), funded_account());
+        CampaignBuilder {
+            program_test,
+            roles: all_roles(global_state, admin.pubkey()),
+            state,
+            admin,
+            mint,
+            token_program: spl_token::id(),
+            supply: 0,
+        }
+    }
+
+    /// Moves the campaign to a Token-2022 mint the test creates itself, with the vault at the
+    /// vault authority's ATA
+    pub fn use_token_2022(&mut self) {
+        self.token_program = spl_token_2022::id();
+        self.state.vault = get_associated_token_address_with_program_id(
+            &self.vault_authority(),
+            &self.mint.pubkey(),
+            &self.token_program,
+        );
+    }
+
+    pub fn global_state(&self) -> Pubkey {
+        global_state_address(&self.mint.pubkey()).0
+    }
+
+    pub fn vault_authority(&self) -> Pubkey {
+        vault_authority_address(&self.global_state()).0
+    }
+
+    /// Adds a legacy token account of the campaign mint holding `amount`
+    pub fn add_token_account(&mut self, address: Pubkey, owner: Pubkey, amount: u64) {
+        self.supply += amount;
+        self.program_test.add_account(
+            address,
+            token_program_account(spl_token::id(), token_account_data(self.mint.pubkey(), owner, amount)),
+        );
+    }
+
+    pub fn fund_vault(&mut self, amount: u64) {
+        let (vault, vault_authority) = (self.state.vault, self.vault_authority());
+        self.add_token_account(vault, vault_authority, amount);
+    }
+
+    /// Funded wallet for a signer other than the payer
+    pub fn add_wallet(&mut self) -> Keypair {
+        let wallet = Keypair::new();
+        self.program_test.add_account(wallet.pubkey(), funded_account());
+        wallet
+    }
+
+    pub async fn start(mut self) -> Campaign {
+        let global_state = self.global_state();
+        self.program_test.add_account(global_state, global_state_account(&self.state));
+        self.program_test.add_account(roles_address(&global_state).0, roles_account(&self.roles));
+        if self.token_program == spl_token::id() {
+            self.program_test.add_account(
+                self.mint.pubkey(),
+                token_program_account(spl_token::id(), mint_data(self.supply, self.state.token_decimals)),
+            );
+        }
+
+        Campaign {
+            context: self.program_test.start_with_context().await,
+            global_state,
+            roles: roles_address(&global_state).0,
+            token_mint: self.mint.pubkey(),
+            vault: self.state.vault,
+            vault_authority: vault_authority_address(&global_state).0,
+            token_program: self.token_program,
+            decimals: self.state.token_decimals,
+            admin: self.admin,
+            mint: self.mint,
+        }
+    }
+}
+
+/// Running campaign and the addresses the tests talk to
+pub struct Campaign {
+    pub context: ProgramTestContext,
+    pub admin: Keypair,
+    pub mint: Keypair,
+    pub global_state: Pubkey,
+    pub roles: Pubkey,
+    pub token_mint: Pubkey,
+    pub vault: Pubkey,
+    pub vault_authority: Pubkey,
+    pub token_program: Pubkey,
+    pub decimals: u8,
+}
+
+impl Campaign {
+    pub fn payer(&self) -> Keypair {
+        self.context.payer.insecure_clone()
+    }
+
+    /// Sends `ixs` paid for by the payer and signed by `signers` as well
+    pub async fn send(&mut self, ixs: &[Instruction], signers: &[&Keypair]) -> Result<(), BanksClientError> {
+        // A fresh blockhash keeps repeated identical instructions apart
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let payer = self.payer();
+        let mut all_signers = vec![&payer];
+        all_signers.extend_from_slice(signers);
+        let mut tx = Transaction::new_with_payer(ixs, Some(&payer.pubkey()));
+        tx.sign(&all_signers, blockhash);
+        self.context.banks_client.process_transaction(tx).await
+    }
+
+    /// Sends `ix` signed by the admin
+    pub async fn send_as_admin(&mut self, ix: Instruction) -> Result<(), BanksClientError> {
+        let admin = self.admin.insecure_clone();
+        self.send(&[ix], &[&admin]).await
+    }
+
+    pub async fn account(&mut self, address: Pubkey) -> Option<Account> {
+        self.context.banks_client.get_account(address).await.unwrap()
+    }
+
+    pub async fn load<T: AccountDeserialize>(&mut self, address: Pubkey) -> T {
+        let account = self.account(address).await.unwrap();
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    pub async fn load_state(&mut self) -> GlobalState {
+        self.load(self.global_state).await
+    }
+
+    /// Token balance under either token program, `None` if the account does not exist
+    pub async fn balance(&mut self, token_account: Pubkey) -> Option<u64> {
+        let account = self.account(token_account).await?;
+        let len = spl_token_2022::state::Account::LEN;
+        Some(spl_token_2022::state::Account::unpack_from_slice(&account.data[..len]).unwrap().amount)
+    }
+
+    pub async fn supply(&mut self) -> u64 {
+        let account = self.account(self.token_mint).await.unwrap();
+        let len = spl_token_2022::state::Mint::LEN;
+        spl_token_2022::state::Mint::unpack_from_slice(&account.data[..len]).unwrap().supply
+    }
+
+    pub async fn set_time(&mut self, unix_timestamp: i64) {
+        let clock = self.context.banks_client.get_sysvar::<Clock>().await.unwrap();
+        self.context.set_sysvar(&Clock { unix_timestamp, ..clock });
+    }
+
+    /// ATA of `owner` for the campaign mint
+    pub fn ata(&self, owner: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(owner, &self.token_mint, &self.token_program)
+    }
+
+    /// Creates the Token-2022 mint with its transfer hook pointing at `hook_program_id`, the
+    /// payer as mint and hook authority, and an ATA for each of `owners`
+    pub async fn create_hooked_mint(&mut self, hook_program_id: Pubkey, owners: &[Pubkey]) {
+        let payer = self.payer();
+        let token_program = spl_token_2022::id();
+        let mint_len =
+            ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[ExtensionType::TransferHook])
+                .unwrap();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let mut instructions = vec![
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &self.token_mint,
+                rent.minimum_balance(mint_len),
+                mint_len as u64,
+                &token_program,
+            ),
+            transfer_hook::instruction::initialize(
+                &token_program,
+                &self.token_mint,
+                Some(payer.pubkey()),
+                Some(hook_program_id),
+            )
+            .unwrap(),
+            spl_token_2022::instruction::initialize_mint2(
+                &token_program,
+                &self.token_mint,
+                &payer.pubkey(),
+                None,
+                self.decimals,
+            )
+            .unwrap(),
+        ];
+        for owner in owners {
+            instructions.push(create_associated_token_account(&payer.pubkey(), owner, &self.token_mint, &token_program));
+        }
+        let mint = self.mint.insecure_clone();
+        self.send(&instructions, &[&mint]).await.unwrap();
+    }
+
+    /// Registers `wallets` for `locked_tokens` each as the config admin, passing `atas` as their
+    /// token accounts
+    pub async fn register_users(
+        &mut self,
+        wallets: &[Pubkey],
+        atas: &[Pubkey],
+        locked_tokens: u64,
+    ) -> Result<(), BanksClientError> {
+        let mut accounts = hotwings::accounts::RegisterUsers {
+            global_state: self.global_state,
+            roles: self.roles,
+            authority: self.admin.pubkey(),
+            token_mint: self.token_mint,
+            rent: solana_program::sysvar::rent::id(),
+            system_program: solana_program::system_program::id(),
+            token_program: self.token_program,
+            associated_token_program: spl_associated_token_account::id(),
+        }
+        .to_account_metas(None);
+        for wallet in wallets {
+            accounts.push(AccountMeta::new(user_state_address(&self.global_state, wallet), false));
+        }
+        for (wallet, ata) in wallets.iter().zip(atas) {
+            accounts.push(AccountMeta::new_readonly(*wallet, false));
+            accounts.push(AccountMeta::new(*ata, false));
+        }
+
+        let entries = wallets.iter().map(|wallet| UserEntry { wallet: *wallet, locked_tokens }).collect();
+        let ix = Instruction {
+            program_id: hotwings::id(),
+            accounts,
+            data: hotwings::instruction::RegisterUsers { entries }.data(),
+        };
+        self.send_as_admin(ix).await
+    }
+
+    /// Mints `amount` of the hooked mint into `destination`
+    pub async fn mint_to(&mut self, destination: Pubkey, amount: u64) {
+        let payer = self.payer();
+        let ix = spl_token_2022::instruction::mint_to(
+            &spl_token_2022::id(),
+            &self.token_mint,
+            &destination,
+            &payer.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap();
+        self.send(&[ix], &[]).await.unwrap();
+    }
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{Campaign, CampaignBuilder};
+use hotwings::consts::USER_STATE_SEED;
+use hotwings::structs::GlobalState;
+use solana_program::instruction::Instruction;
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::{account::Account, signature::Keypair, signer::Signer};
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList};
+use spl_transfer_hook_interface::{get_extra_account_metas_address, instruction::ExecuteInstruction};
+use spl_type_length_value::state::TlvStateBorrowed;
+
+/// Campaign whose extra account list predates the user state lookup, holding only the global state
+async fn setup() -> Campaign {
+    let mut builder = CampaignBuilder::new(GlobalState::default());
+    let global_state = builder.global_state();
+
+    // Sized for the current list so the update can grow into it
+    let mut data = vec![0; ExtraAccountMetaList::size_of(2).unwrap()];
+    ExtraAccountMetaList::init::<ExecuteInstruction>(
+        &mut data,
+        &[ExtraAccountMeta::new_with_pubkey(&global_state, false, false).unwrap()],
+    )
+    .unwrap();
+    builder.program_test.add_account(
+        get_extra_account_metas_address(&builder.mint.pubkey(), &hotwings::id()),
+        Account { lamports: 1_000_000_000, data, owner: hotwings::id(), ..Account::default() },
+    );
+    builder.start().await
+}
+
+async fn update(campaign: &mut Campaign, signer: &Keypair) -> Result<(), BanksClientError> {
+    let ix = Instruction {
+        program_id: hotwings::id(),
+        accounts: hotwings::accounts::UpdateExtraAccountMetaList {
+            global_state: campaign.global_state,
+            roles: campaign.roles,
+            authority: signer.pubkey(),
+            extra_account_meta_list: get_extra_account_metas_address(&campaign.token_mint, &hotwings::id()),
+        }
+        .to_account_metas(None),
+        data: hotwings::instruction::UpdateExtraAccountMetaList {}.data(),
+    };
+    campaign.send(&[ix], &[signer]).await
+}
+
+/// Extra accounts currently stored for `Execute`
+async fn stored_metas(campaign: &mut Campaign) -> Vec<ExtraAccountMeta> {
+    let address = get_extra_account_metas_address(&campaign.token_mint, &hotwings::id());
+    let account = campaign.account(address).await.unwrap();
+    let state = TlvStateBorrowed::unpack(&account.data).unwrap();
+    ExtraAccountMetaList::unpack_with_tlv_state::<ExecuteInstruction>(&state).unwrap().data().to_vec()
+}
+
+#[tokio::test]
+async fn test_update_rewrites_the_extra_account_list() {
+    let mut campaign = setup().await;
+    let admin = campaign.admin.insecure_clone();
+    assert_eq!(stored_metas(&mut campaign).await.len(), 1);
+
+    update(&mut campaign, &admin).await.unwrap();
+
+    // The list now also resolves the source owner's user state
+    let user_state = ExtraAccountMeta::new_with_seeds(
+        &[
+            Seed::Literal { bytes: USER_STATE_SEED.to_vec() },
+            Seed::AccountKey { index: 5 },
+            Seed::AccountData { account_index: 0, data_index: 32, length: 32 },
+        ],
+        false,
+        false,
+    )
+    .unwrap();
+    assert_eq!(
+        stored_metas(&mut campaign).await,
+        vec![ExtraAccountMeta::new_with_pubkey(&campaign.global_state, false, false).unwrap(), user_state]
+    );
+}
+
+#[tokio::test]
+async fn test_update_needs_the_config_admin() {
+    let mut campaign = setup().await;
+
+    assert!(update(&mut campaign, &Keypair::new()).await.is_err());
+    assert_eq!(stored_metas(&mut campaign).await.len(), 1);
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{Campaign, CampaignBuilder};
+use hotwings::consts::MAX_MILESTONES;
+use hotwings::structs::{GlobalState, HoldCapMode, Milestone};
+use solana_program::instruction::Instruction;
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::signer::Signer;
+
+/// Whole-token amount that overflows u64 once scaled by 9 decimals
+const OVERFLOWING_UI_AMOUNT: u64 = u64::MAX / 1_000;
+
+/// Campaign with nothing reached yet whose second milestone raises the hold cap to `milestone_hold_cap`
+async fn campaign(hold_cap_mode: HoldCapMode, hold_cap: u64, milestone_hold_cap: u64) -> Campaign {
+    let mut milestones = [Milestone::default(); MAX_MILESTONES];
+    milestones[0] = Milestone { market_cap: 100_000, unlock_percent: 50, sustain_seconds: 0, hold_cap: 0 };
+    milestones[1] = Milestone { market_cap: 200_000, unlock_percent: 100, sustain_seconds: 0, hold_cap: milestone_hold_cap };
+    CampaignBuilder::new(GlobalState {
+        milestones,
+        milestone_count: 2,
+        hold_cap_mode,
+        hold_cap,
+        token_decimals: 9,
+        ..GlobalState::default()
+    })
+    .start()
+    .await
+}
+
+async fn update_hold_cap(campaign: &mut Campaign, hold_cap_mode: HoldCapMode, hold_cap: u64) -> Result<(), BanksClientError> {
+    let ix = Instruction {
+        program_id: hotwings::id(),
+        accounts: hotwings::accounts::UpdateHoldCap {
+            global_state: campaign.global_state,
+            roles: campaign.roles,
+            authority: campaign.admin.pubkey(),
+        }
+        .to_account_metas(None),
+        data: hotwings::instruction::UpdateHoldCap { hold_cap_mode, hold_cap }.data(),
+    };
+    campaign.send_as_admin(ix).await
+}
+
+async fn update_milestones(campaign: &mut Campaign, milestones: Vec<Milestone>) -> Result<(), BanksClientError> {
+    let ix = Instruction {
+        program_id: hotwings::id(),
+        accounts: hotwings::accounts::UpdateMilestones {
+            global_state: campaign.global_state,
+            roles: campaign.roles,
+            authority: campaign.admin.pubkey(),
+        }
+        .to_account_metas(None),
+        data: hotwings::instruction::UpdateMilestones { milestones }.data(),
+    };
+    campaign.send_as_admin(ix).await
+}
+
+#[tokio::test]
+async fn test_milestone_hold_caps_follow_the_mode() {
+    let mut campaign = campaign(HoldCapMode::UiAmount, 1_000, 2_000).await;
+
+    // A cap that no longer fits in raw units once scaled by the decimals
+    let schedule = |hold_cap| {
+        vec![
+            Milestone { market_cap: 100_000, unlock_percent: 50, sustain_seconds: 0, hold_cap: 0 },
+            Milestone { market_cap: 200_000, unlock_percent: 100, sustain_seconds: 0, hold_cap },
+        ]
+    };
+    assert!(update_milestones(&mut campaign, schedule(OVERFLOWING_UI_AMOUNT)).await.is_err());
+
+    update_milestones(&mut campaign, schedule(5_000)).await.unwrap();
+    assert_eq!(campaign.load_state().await.milestones[1].hold_cap, 5_000);
+}
+
+#[tokio::test]
+async fn test_mode_switch_checks_milestone_hold_caps() {
+    let mut campaign = campaign(HoldCapMode::Absolute, 1_000_000, OVERFLOWING_UI_AMOUNT).await;
+
+    // The base cap alone would be fine, the milestone cap no longer is
+    assert!(update_hold_cap(&mut campaign, HoldCapMode::UiAmount, 1_000).await.is_err());
+    assert!(update_hold_cap(&mut campaign, HoldCapMode::SupplyBps, 100).await.is_err());
+    assert_eq!(campaign.load_state().await.hold_cap_mode, HoldCapMode::Absolute);
+
+    update_hold_cap(&mut campaign, HoldCapMode::Absolute, 2_000_000).await.unwrap();
+    assert_eq!(campaign.load_state().await.hold_cap, 2_000_000);
+}
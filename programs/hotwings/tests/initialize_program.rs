@@ -0,0 +1,148 @@
+mod common;
+
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use hotwings::consts::{VAULT_AUTHORITY_SEED, VAULT_SEED};
+use hotwings::structs::{GlobalState, Milestone, Roles};
+use solana_program::{instruction::Instruction, program_option::COption};
+use solana_program_test::{tokio, BanksClient, BanksClientError};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction};
+
+/// Runs `initialize_program` signed by `authority` for a mint whose mint authority is `mint_authority`
+async fn initialize(
+    authority: &Keypair,
+    mint_authority: Pubkey,
+) -> (BanksClient, Result<(), BanksClientError>, Vec<Milestone>, InitializedAccounts) {
+    let program_id = hotwings::id();
+    let mut program_test = common::program_test();
+
+    let burn_wallet = Pubkey::new_unique();
+    let marketing_wallet = Pubkey::new_unique();
+    let project_wallet = Pubkey::new_unique();
+    let token_mint = Pubkey::new_unique();
+    for wallet in [authority.pubkey(), burn_wallet, marketing_wallet, project_wallet] {
+        program_test.add_account(wallet, common::funded_account());
+    }
+    program_test.add_account(
+        token_mint,
+        common::token_program_account(
+            spl_token::id(),
+            common::mint_data_with_authority(COption::Some(mint_authority), 1_000_000_000, 9),
+        ),
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let raydium_program_id = Pubkey::new_unique();
+    let milestones = vec![
+        Milestone { market_cap: 45_000, unlock_percent: 10, sustain_seconds: 0, hold_cap: 0 },
+        Milestone { market_cap: 105_500, unlock_percent: 20, sustain_seconds: 0, hold_cap: 0 },
+        Milestone { market_cap: 225_000, unlock_percent: 30, sustain_seconds: 0, hold_cap: 0 },
+        Milestone { market_cap: 395_000, unlock_percent: 40, sustain_seconds: 0, hold_cap: 0 },
+        Milestone { market_cap: 650_000, unlock_percent: 50, sustain_seconds: 0, hold_cap: 0 },
+        Milestone { market_cap: 997_000, unlock_percent: 60, sustain_seconds: 0, hold_cap: 0 },
+        Milestone { market_cap: 1_574_000, unlock_percent: 70, sustain_seconds: 0, hold_cap: 0 },
+        Milestone { market_cap: 2_500_000, unlock_percent: 100, sustain_seconds: 0, hold_cap: 0 },
+    ];
+
+    // Global state is derived from the mint, the vault PDAs from the global state
+    let (global_state, _) = common::global_state_address(&token_mint);
+    let (roles, _) = common::roles_address(&global_state);
+    let (vault, _) = Pubkey::find_program_address(&[VAULT_SEED, global_state.as_ref()], &program_id);
+    let (vault_authority, _) = Pubkey::find_program_address(&[VAULT_AUTHORITY_SEED, global_state.as_ref()], &program_id);
+
+    let mut ix = Instruction {
+        program_id,
+        accounts: hotwings::accounts::InitializeProgram {
+            global_state,
+            roles,
+            token_mint,
+            burn_wallet,
+            marketing_wallet,
+            project_wallet,
+            vault,
+            vault_authority,
+            authority: authority.pubkey(),
+            system_program: solana_program::system_program::id(),
+            token_program: spl_token::id(),
+            rent: solana_program::sysvar::rent::id(),
+        }
+        .to_account_metas(None),
+        data: hotwings::instruction::InitializeProgram { raydium_program_id, milestones: milestones.clone() }.data(),
+    };
+    // The program insists on writable wallets
+    for meta in &mut ix.accounts[3..6] {
+        meta.is_writable = true;
+    }
+
+    let mut tx = Transaction::new_with_payer(&[ix], Some(&payer.pubkey()));
+    tx.sign(&[&payer, authority], recent_blockhash);
+    let result = banks_client.process_transaction(tx).await;
+
+    let accounts = InitializedAccounts {
+        global_state,
+        roles,
+        token_mint,
+        burn_wallet,
+        marketing_wallet,
+        project_wallet,
+        vault,
+        raydium_program_id,
+    };
+    (banks_client, result, milestones, accounts)
+}
+
+struct InitializedAccounts {
+    global_state: Pubkey,
+    roles: Pubkey,
+    token_mint: Pubkey,
+    burn_wallet: Pubkey,
+    marketing_wallet: Pubkey,
+    project_wallet: Pubkey,
+    vault: Pubkey,
+    raydium_program_id: Pubkey,
+}
+
+#[tokio::test]
+async fn test_initialize_program() {
+    let authority = Keypair::new();
+    let (mut banks_client, result, milestones, accounts) = initialize(&authority, authority.pubkey()).await;
+    result.unwrap();
+    let InitializedAccounts {
+        global_state,
+        roles,
+        token_mint,
+        burn_wallet,
+        marketing_wallet,
+        project_wallet,
+        vault,
+        raydium_program_id,
+    } = accounts;
+
+    let account = banks_client.get_account(global_state).await.unwrap().unwrap();
+    let state = GlobalState::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(state.authority, authority.pubkey());
+    assert_eq!(state.token_mint, token_mint);
+    assert_eq!(state.burn_wallet, burn_wallet);
+    assert_eq!(state.marketing_wallet, marketing_wallet);
+    assert_eq!(state.project_wallet, project_wallet);
+    assert_eq!(state.raydium_program_id, raydium_program_id);
+    assert_eq!(state.vault, vault);
+    assert_eq!(state.milestone_count as usize, milestones.len());
+    assert_eq!(state.milestones[7].market_cap, 2_500_000);
+
+    // Every role starts with the initializer
+    let account = banks_client.get_account(roles).await.unwrap().unwrap();
+    let roles = Roles::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(roles.global_state, global_state);
+    assert_eq!(roles.config_admin, authority.pubkey());
+    assert_eq!(roles.treasury_signer, authority.pubkey());
+}
+
+#[tokio::test]
+async fn test_only_the_mint_authority_can_initialize() {
+    // Someone racing the deployer for the mint's config PDA
+    let (mut banks_client, result, _, accounts) = initialize(&Keypair::new(), Pubkey::new_unique()).await;
+
+    assert!(result.is_err());
+    assert!(banks_client.get_account(accounts.global_state).await.unwrap().is_none());
+}
@@ -0,0 +1,167 @@
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{Campaign, CampaignBuilder};
+use hotwings::consts::{MULTISIG_SEED, PROPOSAL_SEED};
+use hotwings::structs::{AdminAction, BurnMode, GlobalState};
+use solana_program::instruction::Instruction;
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+/// Campaign whose authority hands sensitive changes to a 2-of-3 multisig
+async fn setup() -> (Campaign, [Keypair; 3]) {
+    let mut builder = CampaignBuilder::new(GlobalState::default());
+    // Proposers pay for their proposals
+    let signers = [(); 3].map(|_| builder.add_wallet());
+    let mut campaign = builder.start().await;
+
+    let ix = Instruction {
+        program_id: hotwings::id(),
+        accounts: hotwings::accounts::CreateMultisig {
+            global_state: campaign.global_state,
+            multisig: multisig_address(&campaign),
+            authority: campaign.admin.pubkey(),
+            system_program: solana_program::system_program::id(),
+        }
+        .to_account_metas(None),
+        data: hotwings::instruction::CreateMultisig {
+            signers: signers.iter().map(|signer| signer.pubkey()).collect(),
+            threshold: 2,
+        }
+        .data(),
+    };
+    campaign.send_as_admin(ix).await.unwrap();
+    (campaign, signers)
+}
+
+fn multisig_address(campaign: &Campaign) -> Pubkey {
+    Pubkey::find_program_address(&[MULTISIG_SEED, campaign.global_state.as_ref()], &hotwings::id()).0
+}
+
+fn proposal_address(campaign: &Campaign, index: u64) -> Pubkey {
+    Pubkey::find_program_address(&[PROPOSAL_SEED, multisig_address(campaign).as_ref(), &index.to_le_bytes()], &hotwings::id()).0
+}
+
+fn propose_ix(campaign: &Campaign, proposer: Pubkey, index: u64, action: AdminAction) -> Instruction {
+    Instruction {
+        program_id: hotwings::id(),
+        accounts: hotwings::accounts::ProposeAdminAction {
+            global_state: campaign.global_state,
+            multisig: multisig_address(campaign),
+            proposal: proposal_address(campaign, index),
+            proposer,
+            system_program: solana_program::system_program::id(),
+        }
+        .to_account_metas(None),
+        data: hotwings::instruction::ProposeAdminAction { action }.data(),
+    }
+}
+
+fn approve_ix(campaign: &Campaign, signer: Pubkey, index: u64) -> Instruction {
+    Instruction {
+        program_id: hotwings::id(),
+        accounts: hotwings::accounts::ApproveAdminAction {
+            multisig: multisig_address(campaign),
+            proposal: proposal_address(campaign, index),
+            signer,
+        }
+        .to_account_metas(None),
+        data: hotwings::instruction::ApproveAdminAction {}.data(),
+    }
+}
+
+fn execute_ix(campaign: &Campaign, executor: Pubkey, index: u64) -> Instruction {
+    Instruction {
+        program_id: hotwings::id(),
+        accounts: hotwings::accounts::ExecuteAdminAction {
+            global_state: campaign.global_state,
+            multisig: multisig_address(campaign),
+            proposal: proposal_address(campaign, index),
+            executor,
+        }
+        .to_account_metas(None),
+        data: hotwings::instruction::ExecuteAdminAction {}.data(),
+    }
+}
+
+#[tokio::test]
+async fn test_liquidity_pool_change_needs_threshold() {
+    let (mut campaign, [first, second, _]) = setup().await;
+    let pool = Pubkey::new_unique();
+
+    // The single-key path is closed once the multisig exists
+    let authority = campaign.admin.insecure_clone();
+    let direct = Instruction {
+        program_id: hotwings::id(),
+        accounts: hotwings::accounts::UpdateLiquidityPoolAddress {
+            global_state: campaign.global_state,
+            roles: campaign.roles,
+            authority: authority.pubkey(),
+        }
+        .to_account_metas(None),
+        data: hotwings::instruction::UpdateLiquidityPoolAddress { new_liquidity_pool: pool }.data(),
+    };
+    assert!(campaign.send(&[direct], &[&authority]).await.is_err());
+
+    let ix = propose_ix(&campaign, first.pubkey(), 0, AdminAction::UpdateLiquidityPool { liquidity_pool: pool });
+    campaign.send(&[ix], &[&first]).await.unwrap();
+
+    // One approval out of two
+    let ix = execute_ix(&campaign, first.pubkey(), 0);
+    assert!(campaign.send(&[ix], &[&first]).await.is_err());
+
+    let ix = approve_ix(&campaign, first.pubkey(), 0);
+    assert!(campaign.send(&[ix], &[&first]).await.is_err(), "proposer already approved");
+    let ix = approve_ix(&campaign, second.pubkey(), 0);
+    campaign.send(&[ix], &[&second]).await.unwrap();
+
+    let ix = execute_ix(&campaign, second.pubkey(), 0);
+    campaign.send(&[ix], &[&second]).await.unwrap();
+    assert_eq!(campaign.load_state().await.liquidity_pool, pool);
+
+    // A proposal runs once
+    let ix = execute_ix(&campaign, first.pubkey(), 0);
+    assert!(campaign.send(&[ix], &[&first]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_outsiders_cannot_propose() {
+    let (mut campaign, _) = setup().await;
+    let authority = campaign.admin.insecure_clone();
+
+    let ix = propose_ix(
+        &campaign,
+        authority.pubkey(),
+        0,
+        AdminAction::ProposeAuthority { new_authority: Pubkey::new_unique() },
+    );
+    assert!(campaign.send(&[ix], &[&authority]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_burn_mode_change_goes_through_the_multisig() {
+    let (mut campaign, [first, second, _]) = setup().await;
+
+    let authority = campaign.admin.insecure_clone();
+    let direct = Instruction {
+        program_id: hotwings::id(),
+        accounts: hotwings::accounts::UpdateTaxConfig {
+            global_state: campaign.global_state,
+            roles: campaign.roles,
+            authority: authority.pubkey(),
+        }
+        .to_account_metas(None),
+        data: hotwings::instruction::UpdateBurnMode { burn_mode: BurnMode::TransferToBurnWallet }.data(),
+    };
+    assert!(campaign.send(&[direct], &[&authority]).await.is_err());
+
+    let action = AdminAction::UpdateBurnMode { burn_mode: BurnMode::TransferToBurnWallet };
+    let ix = propose_ix(&campaign, first.pubkey(), 0, action);
+    campaign.send(&[ix], &[&first]).await.unwrap();
+    let ix = approve_ix(&campaign, second.pubkey(), 0);
+    campaign.send(&[ix], &[&second]).await.unwrap();
+    let ix = execute_ix(&campaign, second.pubkey(), 0);
+    campaign.send(&[ix], &[&second]).await.unwrap();
+
+    assert_eq!(campaign.load_state().await.burn_mode, BurnMode::TransferToBurnWallet);
+}
@@ -0,0 +1,156 @@
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{Campaign, CampaignBuilder};
+use hotwings::consts::{DEFAULT_TWAP_WINDOW_SECONDS, MAX_MILESTONES};
+use hotwings::structs::{GlobalState, Milestone};
+use solana_program::instruction::Instruction;
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::{pubkey::Pubkey, signer::Signer};
+
+const DECIMALS: u8 = 9;
+const VAULT_BALANCE: u64 = 1_000_000;
+const CRANK_REWARD: u64 = 100;
+/// Market cap of the first milestone
+const FIRST_THRESHOLD: u64 = 100_000;
+
+/// Two-milestone campaign with nothing reached yet and a deadline far off
+fn campaign() -> GlobalState {
+    let mut milestones = [Milestone::default(); MAX_MILESTONES];
+    milestones[0] = Milestone { market_cap: FIRST_THRESHOLD, unlock_percent: 50, sustain_seconds: 0, hold_cap: 0 };
+    milestones[1] = Milestone { market_cap: 200_000, unlock_percent: 100, sustain_seconds: 0, hold_cap: 0 };
+    GlobalState {
+        milestones,
+        milestone_count: 2,
+        three_month_unlock_date: i64::MAX,
+        crank_reward: CRANK_REWARD,
+        token_decimals: DECIMALS,
+        ..GlobalState::default()
+    }
+}
+
+/// Deploys `state` over a funded vault, returning the campaign and the crank caller's token account
+async fn setup(state: GlobalState) -> (Campaign, Pubkey) {
+    let mut builder = CampaignBuilder::new(state);
+    builder.fund_vault(VAULT_BALANCE);
+    let caller_token_account = Pubkey::new_unique();
+    builder.add_token_account(caller_token_account, Pubkey::new_unique(), 0);
+    (builder.start().await, caller_token_account)
+}
+
+/// Campaign whose three month deadline has passed while the market cap sits below the first threshold
+async fn setup_past_deadline(crank_reward_pool: u64) -> (Campaign, Pubkey) {
+    setup(GlobalState { current_market_cap: 1_000, three_month_unlock_date: 1, crank_reward_pool, ..campaign() }).await
+}
+
+async fn crank(campaign: &mut Campaign, caller_token_account: Pubkey) -> Result<(), BanksClientError> {
+    let ix = Instruction {
+        program_id: hotwings::id(),
+        accounts: hotwings::accounts::UnlockTokens {
+            global_state: campaign.global_state,
+            vault: campaign.vault,
+            vault_authority: campaign.vault_authority,
+            caller: campaign.context.payer.pubkey(),
+            caller_token_account,
+            token_mint: campaign.token_mint,
+            token_program: spl_token::id(),
+        }
+        .to_account_metas(None),
+        data: hotwings::instruction::ProcessMilestones {}.data(),
+    };
+    campaign.send(&[ix], &[]).await
+}
+
+/// Oracle updater reports `market_cap` at `unix_timestamp`
+async fn report_market_cap(campaign: &mut Campaign, unix_timestamp: i64, market_cap: u64) {
+    campaign.set_time(unix_timestamp).await;
+    let ix = Instruction {
+        program_id: hotwings::id(),
+        accounts: hotwings::accounts::UpdateMarketCap {
+            global_state: campaign.global_state,
+            roles: campaign.roles,
+            authority: campaign.admin.pubkey(),
+        }
+        .to_account_metas(None),
+        data: hotwings::instruction::UpdateMarketCap { market_cap }.data(),
+    };
+    campaign.send_as_admin(ix).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_deadline_unlocks_the_final_milestone() {
+    let (mut campaign, caller_token_account) = setup_past_deadline(0).await;
+
+    crank(&mut campaign, caller_token_account).await.unwrap();
+
+    let state = campaign.load_state().await;
+    assert!(state.unlock_complete);
+    assert_eq!(state.current_milestone, 2);
+    // Nothing was set aside for rewards, so the user allocations stay untouched
+    assert_eq!(campaign.balance(caller_token_account).await, Some(0));
+}
+
+#[tokio::test]
+async fn test_deadline_unlocks_without_market_cap_readings() {
+    // Default TWAP window, but the oracle never reported anything
+    let state = GlobalState {
+        twap_window_seconds: DEFAULT_TWAP_WINDOW_SECONDS,
+        three_month_unlock_date: 1,
+        ..campaign()
+    };
+    let (mut campaign, caller_token_account) = setup(state).await;
+
+    crank(&mut campaign, caller_token_account).await.unwrap();
+
+    let state = campaign.load_state().await;
+    assert!(state.unlock_complete);
+    assert_eq!(state.current_milestone, 2);
+}
+
+#[tokio::test]
+async fn test_crank_reward_is_paid_from_its_pool() {
+    let (mut campaign, caller_token_account) = setup_past_deadline(CRANK_REWARD + 50).await;
+
+    crank(&mut campaign, caller_token_account).await.unwrap();
+
+    assert_eq!(campaign.balance(caller_token_account).await, Some(CRANK_REWARD));
+    assert_eq!(campaign.load_state().await.crank_reward_pool, 50);
+}
+
+#[tokio::test]
+async fn test_spike_does_not_move_the_twap() {
+    let (mut campaign, caller_token_account) = setup(GlobalState { twap_window_seconds: 600, ..campaign() }).await;
+
+    report_market_cap(&mut campaign, 1_000, 10_000).await;
+    // A single reading far above the threshold right before the crank
+    report_market_cap(&mut campaign, 1_600, 10 * FIRST_THRESHOLD).await;
+    assert!(crank(&mut campaign, caller_token_account).await.is_err());
+    assert_eq!(campaign.load_state().await.current_milestone, 0);
+
+    // Held for the whole window, the average catches up
+    report_market_cap(&mut campaign, 2_200, 10 * FIRST_THRESHOLD).await;
+    crank(&mut campaign, caller_token_account).await.unwrap();
+    assert_eq!(campaign.load_state().await.current_milestone, 2);
+}
+
+#[tokio::test]
+async fn test_milestone_waits_for_the_sustain_period() {
+    let mut state = campaign();
+    state.milestones[0].sustain_seconds = 3_600;
+    let (mut campaign, caller_token_account) = setup(state).await;
+    let above = FIRST_THRESHOLD + 1;
+
+    report_market_cap(&mut campaign, 1_000, above).await;
+    campaign.set_time(2_000).await;
+    assert!(crank(&mut campaign, caller_token_account).await.is_err(), "only above the threshold for 1000s");
+
+    // Dipping below restarts the timer
+    report_market_cap(&mut campaign, 3_000, FIRST_THRESHOLD - 1).await;
+    report_market_cap(&mut campaign, 3_500, above).await;
+    campaign.set_time(4_600).await;
+    assert!(crank(&mut campaign, caller_token_account).await.is_err(), "timer restarted at 3500");
+
+    campaign.set_time(3_500 + 3_600).await;
+    crank(&mut campaign, caller_token_account).await.unwrap();
+    assert_eq!(campaign.load_state().await.current_milestone, 1);
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::CampaignBuilder;
+use hotwings::structs::{GlobalState, PythPrice};
+use solana_program::{clock::Clock, instruction::Instruction};
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::{account::Account, pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const PYTH_ACCOUNT_SIZE: usize = 3312;
+const TOKEN_DECIMALS: u8 = 9;
+
+/// Builds a Pyth v2 price account with only the fields the program reads filled in
+fn pyth_price_account(price: i64, conf: u64, expo: i32, publish_time: i64) -> Vec<u8> {
+    let mut data = vec![0u8; PYTH_ACCOUNT_SIZE];
+    data[0..4].copy_from_slice(&PythPrice::MAGIC.to_le_bytes());
+    data[4..8].copy_from_slice(&PythPrice::VERSION.to_le_bytes());
+    data[8..12].copy_from_slice(&PythPrice::ACCOUNT_TYPE_PRICE.to_le_bytes());
+    data[12..16].copy_from_slice(&(PYTH_ACCOUNT_SIZE as u32).to_le_bytes());
+    data[20..24].copy_from_slice(&expo.to_le_bytes());
+    data[96..104].copy_from_slice(&publish_time.to_le_bytes());
+    data[208..216].copy_from_slice(&price.to_le_bytes());
+    data[216..224].copy_from_slice(&conf.to_le_bytes());
+    data[224..228].copy_from_slice(&PythPrice::STATUS_TRADING.to_le_bytes());
+    data
+}
+
+/// Runs `update_market_cap_from_pyth` against a fixture priced `publish_age` seconds ago
+async fn run_pyth_update(price: i64, conf: u64, expo: i32, publish_age: i64) -> (Result<(), BanksClientError>, u64) {
+    let price_feed = Pubkey::new_unique();
+    let mut builder = CampaignBuilder::new(GlobalState {
+        pyth_price_feed: price_feed,
+        max_price_staleness: 60,
+        max_price_confidence_bps: 200,
+        token_decimals: TOKEN_DECIMALS,
+        ..GlobalState::default()
+    });
+    // 1 billion tokens in circulation
+    builder.supply = 1_000_000_000 * 10u64.pow(TOKEN_DECIMALS as u32);
+    let mut campaign = builder.start().await;
+
+    let clock: Clock = campaign.context.banks_client.get_sysvar().await.unwrap();
+    campaign.context.set_account(
+        &price_feed,
+        &Account {
+            lamports: 1_000_000_000,
+            data: pyth_price_account(price, conf, expo, clock.unix_timestamp - publish_age),
+            owner: Keypair::new().pubkey(), // Stand-in for the Pyth oracle program
+            ..Account::default()
+        }
+        .into(),
+    );
+
+    let ix = Instruction {
+        program_id: hotwings::id(),
+        accounts: hotwings::accounts::UpdateMarketCapFromPyth {
+            global_state: campaign.global_state,
+            price_feed,
+            token_mint: campaign.token_mint,
+            caller: campaign.context.payer.pubkey(),
+        }
+        .to_account_metas(None),
+        data: hotwings::instruction::UpdateMarketCapFromPyth {}.data(),
+    };
+    let result = campaign.send(&[ix], &[]).await;
+    (result, campaign.load_state().await.current_market_cap)
+}
+
+#[tokio::test]
+async fn test_market_cap_from_pyth_price() {
+    // $0.001 per token (100_000 x 10^-8) across 1 billion tokens
+    let (result, market_cap) = run_pyth_update(100_000, 1_000, -8, 5).await;
+    result.unwrap();
+    assert_eq!(market_cap, 1_000_000);
+}
+
+#[tokio::test]
+async fn test_stale_pyth_price_is_rejected() {
+    let (result, market_cap) = run_pyth_update(100_000, 1_000, -8, 3_600).await;
+    assert!(result.is_err());
+    assert_eq!(market_cap, 0);
+}
+
+#[tokio::test]
+async fn test_wide_pyth_confidence_is_rejected() {
+    // Confidence of 5% is wider than the 2% allowed
+    let (result, market_cap) = run_pyth_update(100_000, 5_000, -8, 5).await;
+    assert!(result.is_err());
+    assert_eq!(market_cap, 0);
+}
+
+#[test]
+fn test_market_cap_scaling() {
+    // Positive exponents scale up, decimals scale down
+    assert_eq!(hotwings::market_cap_from_price(2, 3, 5_000, 2).unwrap(), 100_000);
+    assert_eq!(hotwings::market_cap_from_price(150_000_000, -8, 10_000_000_000, 9).unwrap(), 15);
+}
@@ -0,0 +1,156 @@
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::CampaignBuilder;
+use hotwings::structs::{GlobalState, RaydiumAmmPool, Roles};
+use solana_program::instruction::Instruction;
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::{account::Account, pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const TOKEN_DECIMALS: u8 = 9;
+const USDC_DECIMALS: u8 = 6;
+
+/// Builds a Raydium AMM v4 pool account with only the fields the program reads filled in
+fn amm_pool_data(coin_vault: Pubkey, pc_vault: Pubkey, coin_mint: Pubkey, pc_mint: Pubkey) -> Vec<u8> {
+    let mut data = vec![0u8; RaydiumAmmPool::LEN];
+    data[32..40].copy_from_slice(&(TOKEN_DECIMALS as u64).to_le_bytes());
+    data[40..48].copy_from_slice(&(USDC_DECIMALS as u64).to_le_bytes());
+    data[336..368].copy_from_slice(coin_vault.as_ref());
+    data[368..400].copy_from_slice(pc_vault.as_ref());
+    data[400..432].copy_from_slice(coin_mint.as_ref());
+    data[432..464].copy_from_slice(pc_mint.as_ref());
+    data
+}
+
+fn mint_account(supply: u64, decimals: u8) -> Account {
+    common::token_program_account(spl_token::id(), common::mint_data(supply, decimals))
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    common::token_program_account(spl_token::id(), common::token_account_data(mint, owner, amount))
+}
+
+struct PoolScenario {
+    /// Give the fabricated pool account an owner other than the configured Raydium program
+    foreign_pool_owner: bool,
+    /// Swap the quote vault for an unrelated token account holding the same mint
+    spoof_quote_vault: bool,
+    /// Push the reading from a wallet that does not hold the oracle updater role
+    unauthorized_caller: bool,
+}
+
+/// Runs `update_market_cap_from_pool` against a pool holding 100M tokens and 50,000 USDC
+async fn run_pool_update(scenario: PoolScenario) -> (Result<(), BanksClientError>, u64) {
+    let raydium_program_id = Pubkey::new_unique();
+    let mut builder = CampaignBuilder::new(GlobalState {
+        raydium_program_id,
+        liquidity_pool: Pubkey::new_unique(),
+        token_decimals: TOKEN_DECIMALS,
+        ..GlobalState::default()
+    });
+    let oracle_updater = builder.add_wallet();
+    builder.roles = Roles {
+        oracle_updater: oracle_updater.pubkey(),
+        ..common::all_roles(builder.global_state(), Pubkey::new_unique())
+    };
+    let caller = if scenario.unauthorized_caller { Keypair::new() } else { oracle_updater };
+
+    let token_mint = builder.mint.pubkey();
+    let usdc_mint = Pubkey::new_unique();
+    let base_vault = Pubkey::new_unique();
+    let quote_vault = Pubkey::new_unique();
+    let spoofed_vault = Pubkey::new_unique();
+    let amm_authority = Pubkey::new_unique();
+
+    // 1 billion tokens in circulation
+    builder.supply = 1_000_000_000 * 10u64.pow(TOKEN_DECIMALS as u32);
+    builder.program_test.add_account(usdc_mint, mint_account(u64::MAX / 2, USDC_DECIMALS));
+    builder.program_test.add_account(
+        base_vault,
+        token_account(token_mint, amm_authority, 100_000_000 * 10u64.pow(TOKEN_DECIMALS as u32)),
+    );
+    builder.program_test.add_account(
+        quote_vault,
+        token_account(usdc_mint, amm_authority, 50_000 * 10u64.pow(USDC_DECIMALS as u32)),
+    );
+    builder.program_test.add_account(
+        spoofed_vault,
+        token_account(usdc_mint, amm_authority, 5_000_000 * 10u64.pow(USDC_DECIMALS as u32)),
+    );
+    builder.program_test.add_account(
+        builder.state.liquidity_pool,
+        Account {
+            lamports: 1_000_000_000,
+            data: amm_pool_data(base_vault, quote_vault, token_mint, usdc_mint),
+            owner: if scenario.foreign_pool_owner { Pubkey::new_unique() } else { raydium_program_id },
+            ..Account::default()
+        },
+    );
+    let mut campaign = builder.start().await;
+
+    let ix = Instruction {
+        program_id: hotwings::id(),
+        accounts: hotwings::accounts::UpdateMarketCapFromPool {
+            global_state: campaign.global_state,
+            roles: campaign.roles,
+            liquidity_pool: campaign.load_state().await.liquidity_pool,
+            base_vault,
+            quote_vault: if scenario.spoof_quote_vault { spoofed_vault } else { quote_vault },
+            token_mint,
+            caller: caller.pubkey(),
+        }
+        .to_account_metas(None),
+        data: hotwings::instruction::UpdateMarketCapFromPool {}.data(),
+    };
+    let result = campaign.send(&[ix], &[&caller]).await;
+    (result, campaign.load_state().await.current_market_cap)
+}
+
+#[tokio::test]
+async fn test_market_cap_from_pool_reserves() {
+    // 50,000 USDC / 100M tokens = $0.0005 per token, times 1 billion tokens
+    let (result, market_cap) = run_pool_update(PoolScenario {
+        foreign_pool_owner: false,
+        spoof_quote_vault: false,
+        unauthorized_caller: false,
+    })
+    .await;
+    result.unwrap();
+    assert_eq!(market_cap, 500_000);
+}
+
+#[tokio::test]
+async fn test_pool_not_owned_by_raydium_is_rejected() {
+    let (result, market_cap) = run_pool_update(PoolScenario {
+        foreign_pool_owner: true,
+        spoof_quote_vault: false,
+        unauthorized_caller: false,
+    })
+    .await;
+    assert!(result.is_err());
+    assert_eq!(market_cap, 0);
+}
+
+#[tokio::test]
+async fn test_vault_not_in_pool_is_rejected() {
+    let (result, market_cap) = run_pool_update(PoolScenario {
+        foreign_pool_owner: false,
+        spoof_quote_vault: true,
+        unauthorized_caller: false,
+    })
+    .await;
+    assert!(result.is_err());
+    assert_eq!(market_cap, 0);
+}
+
+#[tokio::test]
+async fn test_pool_reading_needs_the_oracle_updater() {
+    let (result, market_cap) = run_pool_update(PoolScenario {
+        foreign_pool_owner: false,
+        spoof_quote_vault: false,
+        unauthorized_caller: true,
+    })
+    .await;
+    assert!(result.is_err());
+    assert_eq!(market_cap, 0);
+}
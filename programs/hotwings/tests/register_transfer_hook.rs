@@ -0,0 +1,127 @@
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{Campaign, CampaignBuilder};
+use hotwings::errors::ErrorCode;
+use hotwings::structs::GlobalState;
+use solana_program::{
+    instruction::{Instruction, InstructionError},
+    program_option::COption,
+    system_instruction,
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signer::Signer, transaction::TransactionError};
+use spl_token_2022::extension::{transfer_hook::TransferHook, BaseStateWithExtensions, ExtensionType, StateWithExtensions};
+
+/// Separate program the mint's transfers are hooked to
+const HOOK_PROGRAM_ID: Pubkey = Pubkey::new_from_array([8; 32]);
+
+/// Register or clear instruction signed by `authority`
+fn hook_instruction(campaign: &Campaign, authority: Pubkey, data: Vec<u8>) -> Instruction {
+    Instruction {
+        program_id: hotwings::id(),
+        accounts: hotwings::accounts::RegisterHook {
+            global_state: campaign.global_state,
+            roles: campaign.roles,
+            token_mint: campaign.token_mint,
+            authority,
+            token_program: spl_token_2022::id(),
+        }
+        .to_account_metas(None),
+        data,
+    }
+}
+
+/// Program currently set in the mint's TransferHook extension
+async fn hook_program_id(campaign: &mut Campaign) -> Option<Pubkey> {
+    let account = campaign.account(campaign.token_mint).await.unwrap();
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&account.data).unwrap();
+    let extension = mint.get_extension::<TransferHook>().unwrap();
+    Option::<Pubkey>::from(extension.program_id)
+}
+
+#[tokio::test]
+async fn test_transfer_hook_initialize_clear_and_register() {
+    let mut builder = CampaignBuilder::new(GlobalState { token_decimals: 9, ..GlobalState::default() });
+    builder.use_token_2022();
+    let mut campaign = builder.start().await;
+    let payer = campaign.context.payer.pubkey();
+    let mint = campaign.mint.insecure_clone();
+    let admin = campaign.admin.insecure_clone();
+
+    let token_program = spl_token_2022::id();
+    let mint_len =
+        ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[ExtensionType::TransferHook]).unwrap();
+    let rent = campaign.context.banks_client.get_rent().await.unwrap();
+
+    // The hook is set between creating the account and initializing the mint
+    let instructions = [
+        system_instruction::create_account(
+            &payer,
+            &mint.pubkey(),
+            rent.minimum_balance(mint_len),
+            mint_len as u64,
+            &token_program,
+        ),
+        Instruction {
+            program_id: hotwings::id(),
+            accounts: hotwings::accounts::InitializeHook {
+                token_mint: mint.pubkey(),
+                authority: admin.pubkey(),
+                token_program,
+            }
+            .to_account_metas(None),
+            data: hotwings::instruction::InitializeTransferHook { hook_program_id: HOOK_PROGRAM_ID }.data(),
+        },
+        spl_token_2022::instruction::initialize_mint2(&token_program, &mint.pubkey(), &payer, None, 9).unwrap(),
+    ];
+    campaign.send(&instructions, &[&mint, &admin]).await.unwrap();
+
+    let account = campaign.account(mint.pubkey()).await.unwrap();
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&account.data).unwrap();
+    assert_eq!(state.base.mint_authority, COption::Some(payer));
+    assert_eq!(Option::<Pubkey>::from(state.get_extension::<TransferHook>().unwrap().authority), Some(admin.pubkey()));
+    assert_eq!(hook_program_id(&mut campaign).await, Some(HOOK_PROGRAM_ID));
+
+    // Clearing and registering again both go through the extension's Update instruction
+    let ix = hook_instruction(&campaign, admin.pubkey(), hotwings::instruction::ClearTransferHook {}.data());
+    campaign.send_as_admin(ix).await.unwrap();
+    assert_eq!(hook_program_id(&mut campaign).await, None);
+
+    let data = hotwings::instruction::RegisterTransferHook { hook_program_id: HOOK_PROGRAM_ID }.data();
+    let ix = hook_instruction(&campaign, admin.pubkey(), data);
+    campaign.send_as_admin(ix).await.unwrap();
+    assert_eq!(hook_program_id(&mut campaign).await, Some(HOOK_PROGRAM_ID));
+}
+
+#[tokio::test]
+async fn test_hook_changes_need_the_config_admin() {
+    let mut builder = CampaignBuilder::new(GlobalState { token_decimals: 9, ..GlobalState::default() });
+    builder.use_token_2022();
+    let mut campaign = builder.start().await;
+    let payer = campaign.payer();
+    // The payer is the hook authority on the mint but holds no role in the campaign
+    campaign.create_hooked_mint(HOOK_PROGRAM_ID, &[]).await;
+
+    let ix = hook_instruction(&campaign, payer.pubkey(), hotwings::instruction::ClearTransferHook {}.data());
+    assert!(campaign.send(&[ix], &[]).await.is_err());
+    assert_eq!(hook_program_id(&mut campaign).await, Some(HOOK_PROGRAM_ID));
+}
+
+#[tokio::test]
+async fn test_hotwings_cannot_hook_its_own_mint() {
+    let mut builder = CampaignBuilder::new(GlobalState { token_decimals: 9, ..GlobalState::default() });
+    builder.use_token_2022();
+    let mut campaign = builder.start().await;
+    let admin = campaign.admin.pubkey();
+    campaign.create_hooked_mint(HOOK_PROGRAM_ID, &[]).await;
+
+    let data = hotwings::instruction::RegisterTransferHook { hook_program_id: hotwings::id() }.data();
+    let ix = hook_instruction(&campaign, admin, data);
+    let err = campaign.send_as_admin(ix).await.unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ErrorCode::SelfTransferHook.into()))
+    );
+    assert_eq!(hook_program_id(&mut campaign).await, Some(HOOK_PROGRAM_ID));
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::{Campaign, CampaignBuilder};
+use hotwings::errors::ErrorCode;
+use hotwings::structs::{GlobalState, HoldCapMode, MilestoneUnlockAccount};
+use solana_program::instruction::InstructionError;
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signer::Signer, transaction::TransactionError};
+use spl_associated_token_account::get_associated_token_address;
+
+const LOCKED_TOKENS: u64 = 1_000_000;
+/// Balance already sitting in the ATA of the user who has one
+const EXISTING_BALANCE: u64 = 5;
+
+/// Campaign with two users to register: the first has no ATA yet, the second already holds
+/// tokens in theirs
+async fn setup() -> (Campaign, [Pubkey; 2]) {
+    let mut builder = CampaignBuilder::new(GlobalState {
+        hold_cap_mode: HoldCapMode::Absolute,
+        hold_cap: u64::MAX,
+        token_decimals: 6,
+        ..GlobalState::default()
+    });
+    let wallets = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let existing_ata = get_associated_token_address(&wallets[1], &builder.mint.pubkey());
+    builder.add_token_account(existing_ata, wallets[1], EXISTING_BALANCE);
+    (builder.start().await, wallets)
+}
+
+#[tokio::test]
+async fn test_register_creates_missing_atas() {
+    let (mut campaign, wallets) = setup().await;
+    let atas = wallets.map(|wallet| get_associated_token_address(&wallet, &campaign.token_mint));
+    assert_eq!(campaign.balance(atas[0]).await, None);
+
+    campaign.register_users(&wallets, &atas, LOCKED_TOKENS).await.unwrap();
+
+    // The missing ATA is created, the existing one is left alone
+    assert_eq!(campaign.balance(atas[0]).await, Some(0));
+    assert_eq!(campaign.balance(atas[1]).await, Some(EXISTING_BALANCE));
+
+    for (wallet, ata) in wallets.iter().zip(atas) {
+        let address = common::user_state_address(&campaign.global_state, wallet);
+        let user_state: MilestoneUnlockAccount = campaign.load(address).await;
+        assert_eq!(user_state.wallet, *wallet);
+        assert_eq!(user_state.ata, ata);
+        assert_eq!(user_state.allocated_tokens, LOCKED_TOKENS);
+    }
+}
+
+#[tokio::test]
+async fn test_register_rejects_a_foreign_token_account() {
+    let (mut campaign, wallets) = setup().await;
+
+    assert!(campaign.register_users(&wallets[..1], &[Pubkey::new_unique()], LOCKED_TOKENS).await.is_err());
+
+    let address = common::user_state_address(&campaign.global_state, &wallets[0]);
+    assert!(campaign.account(address).await.is_none());
+}
+
+#[tokio::test]
+async fn test_register_rejects_a_zero_allocation() {
+    let (mut campaign, wallets) = setup().await;
+    let ata = get_associated_token_address(&wallets[0], &campaign.token_mint);
+
+    let err = campaign.register_users(&wallets[..1], &[ata], 0).await.unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ErrorCode::InvalidAllocation.into()))
+    );
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{Campaign, CampaignBuilder};
+use hotwings::structs::{GlobalState, Role};
+use solana_program::instruction::Instruction;
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+fn manage_roles_ix(campaign: &Campaign, data: Vec<u8>) -> Instruction {
+    Instruction {
+        program_id: hotwings::id(),
+        accounts: hotwings::accounts::ManageRoles {
+            global_state: campaign.global_state,
+            roles: campaign.roles,
+            authority: campaign.admin.pubkey(),
+        }
+        .to_account_metas(None),
+        data,
+    }
+}
+
+fn update_market_cap_ix(campaign: &Campaign, signer: Pubkey, market_cap: u64) -> Instruction {
+    Instruction {
+        program_id: hotwings::id(),
+        accounts: hotwings::accounts::UpdateMarketCap {
+            global_state: campaign.global_state,
+            roles: campaign.roles,
+            authority: signer,
+        }
+        .to_account_metas(None),
+        data: hotwings::instruction::UpdateMarketCap { market_cap }.data(),
+    }
+}
+
+#[tokio::test]
+async fn test_oracle_updater_role_can_be_granted_and_revoked() {
+    // The authority still holds every role
+    let mut campaign = CampaignBuilder::new(GlobalState::default()).start().await;
+    let oracle = Keypair::new();
+    let authority = campaign.admin.insecure_clone();
+
+    let ix = manage_roles_ix(
+        &campaign,
+        hotwings::instruction::GrantRole { role: Role::OracleUpdater, holder: oracle.pubkey() }.data(),
+    );
+    campaign.send_as_admin(ix).await.unwrap();
+
+    // The role moved, so the authority alone can no longer push a market cap
+    let ix = update_market_cap_ix(&campaign, authority.pubkey(), 1_000);
+    assert!(campaign.send_as_admin(ix).await.is_err());
+    let ix = update_market_cap_ix(&campaign, oracle.pubkey(), 1_000);
+    campaign.send(&[ix], &[&oracle]).await.unwrap();
+    assert_eq!(campaign.load_state().await.current_market_cap, 1_000);
+
+    let ix = manage_roles_ix(&campaign, hotwings::instruction::RevokeRole { role: Role::OracleUpdater }.data());
+    campaign.send_as_admin(ix).await.unwrap();
+    let ix = update_market_cap_ix(&campaign, oracle.pubkey(), 2_000);
+    assert!(campaign.send(&[ix], &[&oracle]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_only_authority_manages_roles() {
+    let mut campaign = CampaignBuilder::new(GlobalState::default()).start().await;
+    let oracle = Keypair::new();
+
+    // A role holder cannot hand out roles itself
+    let mut ix = manage_roles_ix(
+        &campaign,
+        hotwings::instruction::GrantRole { role: Role::ConfigAdmin, holder: oracle.pubkey() }.data(),
+    );
+    ix.accounts[2].pubkey = oracle.pubkey();
+    assert!(campaign.send(&[ix], &[&oracle]).await.is_err());
+}
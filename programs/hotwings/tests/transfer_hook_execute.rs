@@ -0,0 +1,118 @@
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{Campaign, CampaignBuilder};
+use hotwings::structs::{GlobalState, HoldCapMode};
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::{pubkey::Pubkey, signer::Signer};
+use spl_transfer_hook_interface::get_extra_account_metas_address;
+
+const DECIMALS: u8 = 9;
+const HOLD_CAP: u64 = 1_000;
+
+/// Creates a Token-2022 mint whose transfer hook points at this program, with the payer
+/// holding `HOLD_CAP * 10` tokens, and returns the recipient's empty token account
+async fn setup(exempt_destination: bool) -> (Campaign, Pubkey) {
+    let recipient = Pubkey::new_unique();
+    let mut builder = CampaignBuilder::new(GlobalState {
+        hold_cap_mode: HoldCapMode::Absolute,
+        hold_cap: HOLD_CAP,
+        token_decimals: DECIMALS,
+        ..GlobalState::default()
+    });
+    builder.use_token_2022();
+    if exempt_destination {
+        builder.state.exempted_wallets.push(recipient);
+    }
+    let mut campaign = builder.start().await;
+
+    let ix = Instruction {
+        program_id: hotwings::id(),
+        accounts: hotwings::accounts::InitializeExtraAccountMetaList {
+            global_state: campaign.global_state,
+            roles: campaign.roles,
+            authority: campaign.admin.pubkey(),
+            extra_account_meta_list: get_extra_account_metas_address(&campaign.token_mint, &hotwings::id()),
+            system_program: solana_program::system_program::id(),
+        }
+        .to_account_metas(None),
+        data: hotwings::instruction::InitializeExtraAccountMetaList {}.data(),
+    };
+    campaign.send_as_admin(ix).await.unwrap();
+
+    let payer = campaign.context.payer.pubkey();
+    campaign.create_hooked_mint(hotwings::id(), &[payer, recipient]).await;
+    let source = campaign.ata(&payer);
+    campaign.mint_to(source, HOLD_CAP * 10).await;
+    let destination = campaign.ata(&recipient);
+    (campaign, destination)
+}
+
+/// Sends a Token-2022 `transfer_checked` from the payer with the accounts from the extra account
+/// meta list appended
+async fn transfer(campaign: &mut Campaign, destination: Pubkey, amount: u64) -> Result<(), BanksClientError> {
+    let payer = campaign.context.payer.pubkey();
+    let mut ix = spl_token_2022::instruction::transfer_checked(
+        &spl_token_2022::id(),
+        &campaign.ata(&payer),
+        &campaign.token_mint,
+        &destination,
+        &payer,
+        &[],
+        amount,
+        DECIMALS,
+    )
+    .unwrap();
+    ix.accounts.extend([
+        AccountMeta::new_readonly(campaign.global_state, false),
+        AccountMeta::new_readonly(common::user_state_address(&campaign.global_state, &payer), false),
+        AccountMeta::new_readonly(hotwings::id(), false),
+        AccountMeta::new_readonly(get_extra_account_metas_address(&campaign.token_mint, &hotwings::id()), false),
+    ]);
+    campaign.send(&[ix], &[]).await
+}
+
+#[tokio::test]
+async fn test_transfer_within_hold_cap() {
+    let (mut campaign, destination) = setup(false).await;
+
+    transfer(&mut campaign, destination, HOLD_CAP).await.unwrap();
+    assert_eq!(campaign.balance(destination).await, Some(HOLD_CAP));
+}
+
+#[tokio::test]
+async fn test_transfer_above_hold_cap_is_rejected() {
+    let (mut campaign, destination) = setup(false).await;
+
+    assert!(transfer(&mut campaign, destination, HOLD_CAP + 1).await.is_err());
+    assert_eq!(campaign.balance(destination).await, Some(0));
+}
+
+#[tokio::test]
+async fn test_exempt_destination_skips_hold_cap() {
+    let (mut campaign, destination) = setup(true).await;
+
+    transfer(&mut campaign, destination, HOLD_CAP * 5).await.unwrap();
+    assert_eq!(campaign.balance(destination).await, Some(HOLD_CAP * 5));
+}
+
+#[tokio::test]
+async fn test_execute_outside_transfer_is_rejected() {
+    let (mut campaign, destination) = setup(false).await;
+    let payer = campaign.context.payer.pubkey();
+
+    // Calling the hook directly leaves the transferring flag unset
+    let mut ix = spl_transfer_hook_interface::instruction::execute(
+        &hotwings::id(),
+        &campaign.ata(&payer),
+        &campaign.token_mint,
+        &destination,
+        &payer,
+        &get_extra_account_metas_address(&campaign.token_mint, &hotwings::id()),
+        1,
+    );
+    ix.accounts.push(AccountMeta::new_readonly(campaign.global_state, false));
+
+    assert!(campaign.send(&[ix], &[]).await.is_err());
+}
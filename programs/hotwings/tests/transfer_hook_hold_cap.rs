@@ -0,0 +1,112 @@
+mod common;
+
+use hotwings::consts::RAYDIUM_AMM_AUTHORITY_SEED;
+use hotwings::structs::{GlobalState, HoldCapMode};
+use solana_program::{account_info::AccountInfo, pubkey::Pubkey};
+
+const SUPPLY: u64 = 1_000_000_000;
+
+/// Global state capping every wallet at 1% of the supply
+fn global_state(token_mint: Pubkey) -> GlobalState {
+    GlobalState {
+        token_mint,
+        raydium_program_id: Pubkey::new_unique(),
+        liquidity_pool: Pubkey::new_unique(),
+        vault: Pubkey::new_unique(),
+        burn_wallet: Pubkey::new_unique(),
+        marketing_wallet: Pubkey::new_unique(),
+        project_wallet: Pubkey::new_unique(),
+        treasury_wallet: Pubkey::new_unique(),
+        hold_cap_mode: HoldCapMode::SupplyBps,
+        hold_cap: 100,
+        ..GlobalState::default()
+    }
+}
+
+/// Runs the hold cap check for a destination that holds `balance` after the transfer
+fn check_destination(state: &GlobalState, destination_key: Pubkey, owner: Pubkey, balance: u64) -> anchor_lang::Result<()> {
+    let token_program = spl_token_2022::id();
+
+    let mut mint_lamports = 1_000_000_000;
+    let mut mint = common::mint_data(SUPPLY, 9);
+    let mint_info = AccountInfo::new(
+        &state.token_mint, false, false, &mut mint_lamports, &mut mint, &token_program, false, 0,
+    );
+
+    let mut destination_lamports = 1_000_000_000;
+    let mut destination = common::token_account_data(state.token_mint, owner, balance);
+    let destination_info = AccountInfo::new(
+        &destination_key, false, true, &mut destination_lamports, &mut destination, &token_program, false, 0,
+    );
+
+    hotwings::check_hold_cap(state, &destination_info, &mint_info)
+}
+
+#[test]
+fn test_transfer_above_hold_cap_is_blocked() {
+    let state = global_state(Pubkey::new_unique());
+    let buyer = Pubkey::new_unique();
+
+    // 1% of the supply is allowed, one token more is not
+    check_destination(&state, Pubkey::new_unique(), buyer, SUPPLY / 100).unwrap();
+    assert!(check_destination(&state, Pubkey::new_unique(), buyer, SUPPLY / 100 + 1).is_err());
+}
+
+#[test]
+fn test_exempted_owner_is_not_capped() {
+    let mut state = global_state(Pubkey::new_unique());
+    let whale = Pubkey::new_unique();
+    state.exempted_wallets.push(whale);
+
+    check_destination(&state, Pubkey::new_unique(), whale, SUPPLY / 2).unwrap();
+}
+
+#[test]
+fn test_vault_burn_wallet_and_pools_are_whitelisted() {
+    let state = global_state(Pubkey::new_unique());
+    let (amm_authority, _) =
+        Pubkey::find_program_address(&[RAYDIUM_AMM_AUTHORITY_SEED], &state.raydium_program_id);
+
+    check_destination(&state, state.vault, Pubkey::new_unique(), SUPPLY / 2).unwrap();
+    check_destination(&state, Pubkey::new_unique(), state.burn_wallet, SUPPLY / 2).unwrap();
+    check_destination(&state, Pubkey::new_unique(), amm_authority, SUPPLY / 2).unwrap();
+}
+
+#[test]
+fn test_project_wallets_are_whitelisted() {
+    let state = global_state(Pubkey::new_unique());
+
+    for wallet in [state.marketing_wallet, state.project_wallet, state.treasury_wallet] {
+        check_destination(&state, Pubkey::new_unique(), wallet, SUPPLY / 2).unwrap();
+    }
+}
+
+#[test]
+fn test_unset_treasury_wallet_is_not_whitelisted() {
+    let mut state = global_state(Pubkey::new_unique());
+    state.treasury_wallet = Pubkey::default();
+
+    assert!(check_destination(&state, Pubkey::new_unique(), Pubkey::default(), SUPPLY / 2).is_err());
+}
+
+#[test]
+fn test_hold_cap_in_whole_tokens() {
+    let mut state = global_state(Pubkey::new_unique());
+    state.hold_cap_mode = HoldCapMode::UiAmount;
+    state.hold_cap = 2;
+    state.token_decimals = 6;
+    let buyer = Pubkey::new_unique();
+
+    // 2 whole tokens at 6 decimals
+    check_destination(&state, Pubkey::new_unique(), buyer, 2_000_000).unwrap();
+    assert!(check_destination(&state, Pubkey::new_unique(), buyer, 2_000_001).is_err());
+}
+
+#[test]
+fn test_hold_cap_lifts_after_full_unlock() {
+    let mut state = global_state(Pubkey::new_unique());
+    state.unlock_complete = true;
+
+    // Same balance the cap rejects while tokens are still locked
+    check_destination(&state, Pubkey::new_unique(), Pubkey::new_unique(), SUPPLY / 100 + 1).unwrap();
+}
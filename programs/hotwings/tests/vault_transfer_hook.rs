@@ -0,0 +1,165 @@
+mod common;
+
+use anchor_lang::{AccountSerialize, InstructionData, ToAccountMetas};
+use common::{Campaign, CampaignBuilder};
+use hotwings::consts::MAX_MILESTONES;
+use hotwings::errors::ErrorCode;
+use hotwings::structs::{GlobalState, HoldCapMode, Milestone, MilestoneUnlockAccount};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, instruction::{AccountMeta, Instruction, InstructionError},
+    program_error::ProgramError,
+};
+use solana_program_test::{processor, tokio, BanksClientError};
+use solana_sdk::{account::Account, pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::TransactionError};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, state::ExtraAccountMetaList};
+use spl_transfer_hook_interface::{
+    get_extra_account_metas_address,
+    instruction::{ExecuteInstruction, TransferHookInstruction},
+};
+
+const DECIMALS: u8 = 9;
+const ALLOCATION: u64 = 1_000_000;
+/// Program id the stand-in hook is deployed at
+const HOOK_PROGRAM_ID: Pubkey = Pubkey::new_from_array([8; 32]);
+/// Extra account the stand-in hook's validation account asks for
+const HOOK_MARKER: Pubkey = Pubkey::new_from_array([7; 32]);
+
+/// Stand-in transfer hook that fails unless Token-2022 resolved its extra account
+fn hook_stand_in(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    match TransferHookInstruction::unpack(data)? {
+        TransferHookInstruction::Execute { .. } if accounts.len() == 6 && accounts[5].key == &HOOK_MARKER => Ok(()),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+/// Campaign on a Token-2022 mint hooked to `hook_program_id`, one milestone reached and a user
+/// owed their full allocation
+async fn setup(hook_program_id: Pubkey) -> (Campaign, Keypair) {
+    let mut milestones = [Milestone::default(); MAX_MILESTONES];
+    milestones[0] = Milestone { market_cap: 1, unlock_percent: 100, sustain_seconds: 0, hold_cap: 0 };
+    let mut builder = CampaignBuilder::new(GlobalState {
+        burn_wallet: Pubkey::new_unique(),
+        marketing_wallet: Pubkey::new_unique(),
+        milestones,
+        milestone_count: 1,
+        current_milestone: 1,
+        tax_bps: 100,
+        burn_share_bps: 5_000,
+        marketing_share_bps: 5_000,
+        hold_cap_mode: HoldCapMode::Absolute,
+        hold_cap: u64::MAX,
+        token_decimals: DECIMALS,
+        outstanding_allocations: ALLOCATION,
+        ..GlobalState::default()
+    });
+    builder.use_token_2022();
+    builder.program_test.add_program("hook_stand_in", HOOK_PROGRAM_ID, processor!(hook_stand_in));
+
+    let user = Keypair::new();
+    let mint = builder.mint.pubkey();
+    let mut user_state_data = Vec::new();
+    MilestoneUnlockAccount {
+        wallet: user.pubkey(),
+        ata: get_associated_token_address_with_program_id(&user.pubkey(), &mint, &spl_token_2022::id()),
+        allocated_tokens: ALLOCATION,
+        total_locked_tokens: ALLOCATION,
+        unlocked_tokens: 0,
+        deferred_tokens: 0,
+        last_unlocked_milestone: 0,
+    }
+    .try_serialize(&mut user_state_data)
+    .unwrap();
+    builder.program_test.add_account(
+        common::user_state_address(&builder.global_state(), &user.pubkey()),
+        common::program_account(user_state_data),
+    );
+
+    let mut validation_data = vec![0; ExtraAccountMetaList::size_of(1).unwrap()];
+    ExtraAccountMetaList::init::<ExecuteInstruction>(
+        &mut validation_data,
+        &[ExtraAccountMeta::new_with_pubkey(&HOOK_MARKER, false, false).unwrap()],
+    )
+    .unwrap();
+    builder.program_test.add_account(
+        get_extra_account_metas_address(&mint, &HOOK_PROGRAM_ID),
+        Account { lamports: 1_000_000_000, data: validation_data, owner: HOOK_PROGRAM_ID, ..Account::default() },
+    );
+
+    let owners = [builder.vault_authority(), user.pubkey(), builder.state.burn_wallet, builder.state.marketing_wallet];
+    let mut campaign = builder.start().await;
+    campaign.create_hooked_mint(hook_program_id, &owners).await;
+    let vault = campaign.vault;
+    campaign.mint_to(vault, ALLOCATION).await;
+    (campaign, user)
+}
+
+/// Accounts Token-2022 needs to call the stand-in hook
+fn hook_accounts(campaign: &Campaign) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(get_extra_account_metas_address(&campaign.token_mint, &HOOK_PROGRAM_ID), false),
+        AccountMeta::new_readonly(HOOK_MARKER, false),
+        AccountMeta::new_readonly(HOOK_PROGRAM_ID, false),
+    ]
+}
+
+async fn claim(campaign: &mut Campaign, user: &Keypair, hook_accounts: &[AccountMeta]) -> Result<(), BanksClientError> {
+    let state = campaign.load_state().await;
+    let mut accounts = hotwings::accounts::Claim {
+        global_state: campaign.global_state,
+        user: user.pubkey(),
+        user_state: common::user_state_address(&campaign.global_state, &user.pubkey()),
+        user_ata: campaign.ata(&user.pubkey()),
+        vault: campaign.vault,
+        vault_authority: campaign.vault_authority,
+        burn_wallet: campaign.ata(&state.burn_wallet),
+        marketing_wallet: campaign.ata(&state.marketing_wallet),
+        token_mint: campaign.token_mint,
+        treasury_wallet: None,
+        token_program: spl_token_2022::id(),
+    }
+    .to_account_metas(None);
+    accounts.extend_from_slice(hook_accounts);
+    let ix = Instruction { program_id: hotwings::id(), accounts, data: hotwings::instruction::Claim {}.data() };
+    campaign.send(&[ix], &[user]).await
+}
+
+#[tokio::test]
+async fn test_claim_from_hooked_mint() {
+    let (mut campaign, user) = setup(HOOK_PROGRAM_ID).await;
+    let hook_accounts = hook_accounts(&campaign);
+
+    claim(&mut campaign, &user, &hook_accounts).await.unwrap();
+
+    // 1% tax on the full allocation
+    let user_ata = campaign.ata(&user.pubkey());
+    assert_eq!(campaign.balance(user_ata).await, Some(ALLOCATION - ALLOCATION / 100));
+}
+
+#[tokio::test]
+async fn test_claim_without_hook_accounts_fails() {
+    let (mut campaign, user) = setup(HOOK_PROGRAM_ID).await;
+
+    assert!(claim(&mut campaign, &user, &[]).await.is_err());
+    let user_ata = campaign.ata(&user.pubkey());
+    assert_eq!(campaign.balance(user_ata).await, Some(0));
+}
+
+#[tokio::test]
+async fn test_claim_rejects_a_mint_hooked_to_hotwings() {
+    let (mut campaign, user) = setup(hotwings::id()).await;
+    let hook_accounts = [
+        AccountMeta::new_readonly(get_extra_account_metas_address(&campaign.token_mint, &hotwings::id()), false),
+        AccountMeta::new_readonly(campaign.global_state, false),
+        AccountMeta::new_readonly(hotwings::id(), false),
+    ];
+
+    // Token-2022 would call back into hotwings mid-payout, so the claim stops before the transfer
+    let err = claim(&mut campaign, &user, &hook_accounts).await.unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ErrorCode::SelfTransferHook.into()))
+    );
+    let user_ata = campaign.ata(&user.pubkey());
+    assert_eq!(campaign.balance(user_ata).await, Some(0));
+}